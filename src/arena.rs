@@ -0,0 +1,182 @@
+use core::alloc::Layout;
+use core::num::NonZeroUsize;
+use core::ptr::NonNull;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering;
+
+use crate::Backend;
+use crate::Numa;
+use crate::Page;
+use crate::Reservation;
+use crate::backend::File;
+
+/// Bump allocator over a shared segment of `SIZE` bytes, e.g. for variable-size
+/// structures shared across processes.
+///
+/// The segment is mapped into a [`Reservation`] lazily, a page at a time, as
+/// allocations advance the high-water mark. The high-water mark itself lives in
+/// the first page of the segment, so all processes allocate consistently.
+///
+/// Note: each process maps the arena at a different address, so only offsets
+/// (see [`Arena::offset`]) are meaningful to other processes.
+pub struct Arena<const SIZE: usize> {
+    name: String,
+    file: File,
+    backend: Backend,
+    numa: Option<Numa>,
+    reservation: Reservation<SIZE>,
+    // Bytes of the segment mapped into this process's reservation.
+    committed: AtomicUsize,
+}
+
+unsafe impl<const SIZE: usize> Sync for Arena<SIZE> {}
+unsafe impl<const SIZE: usize> Send for Arena<SIZE> {}
+
+#[cfg(feature = "builder")]
+#[bon::bon]
+impl<const SIZE: usize> Arena<SIZE> {
+    #[builder]
+    pub fn new(
+        name: String,
+        #[builder(default)] create: bool,
+        /// Defaults to [`Backend::Shm`].
+        #[builder(into)]
+        backend: Option<Backend>,
+        /// Applied to each page as it is mapped.
+        numa: Option<Numa>,
+    ) -> crate::Result<Self> {
+        Self::with_options(name, create, backend, numa)
+    }
+}
+
+impl<const SIZE: usize> Arena<SIZE> {
+    /// Shorthand for `Arena::builder().name(name).create(create).build()`.
+    pub fn named(name: String, create: bool) -> crate::Result<Self> {
+        Self::with_options(name, create, None, None)
+    }
+
+    fn with_options(
+        name: String,
+        create: bool,
+        backend: Option<Backend>,
+        numa: Option<Numa>,
+    ) -> crate::Result<Self> {
+        let backend = backend.unwrap_or(Backend::Shm(crate::backend::Shm::default()));
+
+        if create {
+            match backend.unlink(&name) {
+                Ok(()) => log::info!("Unlinked stale shm object: {}", name),
+                Err(error) if error.is_not_found() => (),
+                Err(error) => return Err(error),
+            }
+        }
+
+        let file = backend.open(&name, Reservation::<SIZE>::SIZE)?;
+        let arena = Self {
+            name,
+            file,
+            backend,
+            numa,
+            reservation: Reservation::new()?,
+            committed: AtomicUsize::new(0),
+        };
+
+        arena.commit(Page::SIZE)?;
+        Ok(arena)
+    }
+
+    /// Allocate a chunk fitting `layout`, or `None` if the arena is exhausted.
+    ///
+    /// Panics if `layout` requires alignment greater than the page size.
+    pub fn alloc(&self, layout: Layout) -> crate::Result<Option<NonNull<u8>>> {
+        assert!(
+            layout.align() <= Page::SIZE,
+            "Arena alignment can be at most {:#x}, got {:#x}",
+            Page::SIZE,
+            layout.align(),
+        );
+
+        // The first page holds the high-water mark, which starts out zeroed.
+        let chunk = |offset: usize| {
+            let start = offset.max(Page::SIZE).next_multiple_of(layout.align());
+            let end = start.checked_add(layout.size())?;
+            (end <= SIZE).then_some((start, end))
+        };
+
+        let Ok(offset) =
+            self.high_water_mark()
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |offset| {
+                    chunk(offset).map(|(_, end)| end)
+                })
+        else {
+            return Ok(None);
+        };
+
+        let (start, end) = chunk(offset).unwrap();
+        self.commit(end)?;
+        Ok(Some(self.address(start)))
+    }
+
+    /// Bytes allocated so far, across all processes.
+    pub fn allocated(&self) -> usize {
+        self.high_water_mark()
+            .load(Ordering::Acquire)
+            .saturating_sub(Page::SIZE)
+    }
+
+    /// Offset of `pointer` from the start of the arena, which other processes
+    /// can pass to [`Arena::pointer`].
+    pub fn offset(&self, pointer: NonNull<u8>) -> usize {
+        pointer.addr().get() - self.reservation.start().addr().get()
+    }
+
+    /// Pointer to `offset` bytes from the start of the arena in this process,
+    /// mapping any pages allocated by other processes in the meantime.
+    pub fn pointer(&self, offset: usize) -> crate::Result<NonNull<u8>> {
+        assert!(offset < SIZE, "Arena offset {offset:#x} out of bounds");
+        self.commit(
+            self.high_water_mark()
+                .load(Ordering::Acquire)
+                .max(offset + 1),
+        )?;
+        Ok(self.address(offset))
+    }
+
+    pub fn unlink(&mut self) -> crate::Result<()> {
+        self.backend.unlink(&self.name)
+    }
+
+    fn address(&self, offset: usize) -> NonNull<u8> {
+        unsafe { self.reservation.start().cast::<u8>().byte_add(offset) }
+    }
+
+    fn high_water_mark(&self) -> &AtomicUsize {
+        unsafe { self.reservation.start().cast::<AtomicUsize>().as_ref() }
+    }
+
+    // Map the segment into the reservation up to at least `end` bytes.
+    fn commit(&self, end: usize) -> crate::Result<()> {
+        let end = end.next_multiple_of(Page::SIZE);
+        let start = self.committed.load(Ordering::Acquire);
+        if end <= start {
+            return Ok(());
+        }
+
+        // Threads racing to commit overlapping ranges map the same pages of the
+        // same file, so replacing each other's mappings is harmless.
+        let window = self
+            .file
+            .window(start, NonZeroUsize::new(end - start).unwrap())?;
+        self.reservation
+            .place(&window, start, Page::SIZE, self.numa.clone(), None)?;
+
+        self.committed.fetch_max(end, Ordering::AcqRel);
+        Ok(())
+    }
+}
+
+impl<const SIZE: usize> crate::Teardown for Arena<SIZE> {
+    fn unlink(&mut self) -> crate::Result<()> {
+        Arena::unlink(self)
+    }
+}
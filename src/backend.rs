@@ -1,24 +1,36 @@
 #[cfg(feature = "ivshmem")]
 mod ivshmem;
+mod memfd;
 mod mmap;
+mod path;
 pub(crate) mod shm;
 
 #[cfg(feature = "ivshmem")]
 pub use ivshmem::Ivshmem;
+pub use memfd::Memfd;
 pub use mmap::Mmap;
+pub use path::FileBacked;
 pub use shm::Shm;
 
 use core::ffi;
+use core::mem::MaybeUninit;
 use core::num::NonZeroUsize;
 use core::ptr;
 use core::ptr::NonNull;
+use core::str::FromStr;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering;
 use std::os::fd::AsRawFd;
 use std::os::fd::OwnedFd;
 use std::os::unix::prelude::RawFd;
 
+use crate::HugePage;
+use crate::MbindFlags;
 use crate::Numa;
 use crate::Page;
 use crate::Populate;
+use crate::Protection;
 use crate::try_libc;
 
 /// Shared memory backend.
@@ -30,6 +42,8 @@ use crate::try_libc;
 pub enum Backend {
     Mmap(Mmap),
     Shm(Shm),
+    Memfd(Memfd),
+    Path(FileBacked),
     #[cfg(feature = "ivshmem")]
     Ivshmem(Ivshmem),
 }
@@ -39,6 +53,22 @@ impl Backend {
         self.as_backend().open(id, size)
     }
 
+    /// Open the existing segment `id`, failing with `ENOENT` instead of creating it.
+    ///
+    /// Anonymous backends have no existing segments, so always fail.
+    pub fn attach(&self, id: &str, size: NonZeroUsize) -> crate::Result<File> {
+        self.as_backend().attach(id, size)
+    }
+
+    /// Open the existing segment `id`, or return `None` if it doesn't exist.
+    pub fn try_open(&self, id: &str, size: NonZeroUsize) -> crate::Result<Option<File>> {
+        match self.attach(id, size) {
+            Ok(file) => Ok(Some(file)),
+            Err(error) if error.is_not_found() => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
     /// Human-readable name of backend, for debugging purposes.
     pub fn name(&self) -> &str {
         self.as_backend().name()
@@ -52,12 +82,60 @@ impl Backend {
         match self {
             Backend::Mmap(mmap) => mmap,
             Backend::Shm(shm) => shm,
+            Backend::Memfd(memfd) => memfd,
+            Backend::Path(path) => path,
             #[cfg(feature = "ivshmem")]
             Backend::Ivshmem(ivshmem) => ivshmem,
         }
     }
 }
 
+impl Backend {
+    /// Parse the backend named by environment variable `var`, e.g. to switch
+    /// backends between benchmark runs. Returns `None` if `var` is unset.
+    /// See [`Backend::from_str`] for the accepted syntax.
+    pub fn from_env(var: &str) -> crate::Result<Option<Self>> {
+        match std::env::var(var) {
+            Ok(backend) => backend.parse().map(Some),
+            Err(std::env::VarError::NotPresent) => Ok(None),
+            Err(std::env::VarError::NotUnicode(backend)) => Err(crate::Error::UnknownBackend(
+                backend.to_string_lossy().into_owned(),
+            )),
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = crate::Error;
+
+    /// Accepts `mmap`, `shm`, `memfd`, `path:<path>` (see [`FileBacked`]), and
+    /// with the `ivshmem` feature, `ivshmem` or `ivshmem:<device>`.
+    fn from_str(backend: &str) -> Result<Self, Self::Err> {
+        let (kind, argument) = match backend.split_once(':') {
+            Some((kind, argument)) => (kind, Some(argument)),
+            None => (backend, None),
+        };
+
+        match (kind, argument) {
+            ("mmap", None) => Ok(Backend::Mmap(Mmap)),
+            ("shm", None) => Ok(Backend::Shm(Shm::default())),
+            ("memfd", None) => Ok(Backend::Memfd(Memfd)),
+            ("path", Some(path)) => Ok(Backend::Path(FileBacked::new(path))),
+            #[cfg(feature = "ivshmem")]
+            ("ivshmem", device) => match device {
+                None => Ivshmem::new(),
+                Some(device) => Ivshmem::open(std::path::Path::new(device)),
+            }
+            .map(Backend::Ivshmem)
+            .map_err(|source| crate::Error::Libc {
+                name: "open",
+                source,
+            }),
+            _ => Err(crate::Error::UnknownBackend(backend.to_owned())),
+        }
+    }
+}
+
 impl Default for Backend {
     fn default() -> Self {
         Backend::Mmap(Mmap)
@@ -71,6 +149,8 @@ pub(crate) trait Interface: Send + Sync {
 
     fn open(&self, id: &str, size: NonZeroUsize) -> crate::Result<File>;
 
+    fn attach(&self, id: &str, size: NonZeroUsize) -> crate::Result<File>;
+
     fn unlink(&self, id: &str) -> crate::Result<()>;
 }
 
@@ -79,6 +159,7 @@ pub struct File {
     size: NonZeroUsize,
     offset: i64,
     create: bool,
+    protection: Protection,
 }
 
 impl AsRawFd for File {
@@ -88,81 +169,477 @@ impl AsRawFd for File {
 }
 
 impl File {
+    /// Private anonymous memory of `size` bytes, not backed by any file.
+    pub fn anonymous(size: NonZeroUsize) -> Self {
+        Self {
+            fd: None,
+            size,
+            offset: 0,
+            create: true,
+            protection: Protection::ReadWrite,
+        }
+    }
+
+    /// Size of the mapping. For existing objects opened by the shm and path
+    /// backends, this is the size of the object if larger than requested.
+    pub fn size(&self) -> NonZeroUsize {
+        self.size
+    }
+
+    /// Offset of the mapping within the file, e.g. the device allocation
+    /// offset for the ivshmem backend. Zero for the other backends.
+    pub fn offset(&self) -> i64 {
+        self.offset
+    }
+
+    pub(crate) fn protection(&self) -> Protection {
+        self.protection
+    }
+
+    pub(crate) fn set_protection(&mut self, protection: Protection) {
+        self.protection = protection;
+    }
+
+    /// Extend the backing file to `size` bytes, e.g. before growing its mapping.
+    pub(crate) fn resize(&mut self, size: NonZeroUsize) -> crate::Result<()> {
+        let size = NonZeroUsize::new(size.get().next_multiple_of(Page::SIZE)).unwrap();
+        if let Some(fd) = &self.fd {
+            unsafe { try_libc!(libc::ftruncate64(fd.as_raw_fd(), size.get() as i64)) }?;
+        }
+
+        self.size = size;
+        Ok(())
+    }
+
+    /// Duplicate the file descriptor, e.g. to map the same file independently.
+    pub(crate) fn try_clone(&self) -> crate::Result<Self> {
+        let fd = self
+            .fd
+            .as_ref()
+            .map(OwnedFd::try_clone)
+            .transpose()
+            .map_err(|source| crate::Error::Libc {
+                name: "fcntl",
+                source,
+            })?;
+
+        Ok(Self {
+            fd,
+            size: self.size,
+            offset: self.offset,
+            create: false,
+            protection: self.protection,
+        })
+    }
+
+    /// View of `size` bytes starting at `offset` into the file, e.g. to map only
+    /// part of it. Duplicates the file descriptor.
+    pub(crate) fn window(&self, offset: usize, size: NonZeroUsize) -> crate::Result<Self> {
+        let mut file = self.try_clone()?;
+        file.offset += offset as i64;
+        file.size = size;
+        Ok(file)
+    }
+
+    /// Clear `FD_CLOEXEC`, so the descriptor survives `exec`, e.g. to hand
+    /// the segment to a child process. A no-op for anonymous mappings.
+    pub(crate) fn set_inheritable(&self) -> crate::Result<()> {
+        let Some(fd) = &self.fd else {
+            return Ok(());
+        };
+
+        unsafe {
+            let flags = try_libc!(libc::fcntl(fd.as_raw_fd(), libc::F_GETFD))?;
+            try_libc!(libc::fcntl(
+                fd.as_raw_fd(),
+                libc::F_SETFD,
+                flags & !libc::FD_CLOEXEC
+            ))?;
+        }
+        Ok(())
+    }
+
     /// Whether this file is newly created or already existed.
     pub fn is_create(&self) -> bool {
         self.create
     }
 
+    /// Whether this file was opened without write access, in which case
+    /// it is mapped `PROT_READ` by default.
+    pub fn is_readonly(&self) -> bool {
+        self.protection == Protection::ReadOnly
+    }
+
+    /// Metadata of the backing file, or `None` for anonymous mappings.
+    pub(crate) fn stat(&self) -> crate::Result<Option<libc::stat64>> {
+        let Some(fd) = &self.fd else {
+            return Ok(None);
+        };
+
+        let mut stat = MaybeUninit::<libc::stat64>::uninit();
+        unsafe {
+            try_libc!(libc::fstat64(fd.as_raw_fd(), stat.as_mut_ptr()))?;
+            Ok(Some(stat.assume_init()))
+        }
+    }
+
+    // Whether the backing file lives on hugetlbfs, which backs it with huge pages.
+    fn is_hugetlbfs(&self) -> crate::Result<bool> {
+        // From include/uapi/linux/magic.h
+        const HUGETLBFS_MAGIC: u32 = 0x958458f6;
+
+        let Some(fd) = &self.fd else {
+            return Ok(false);
+        };
+
+        let mut statfs = MaybeUninit::<libc::statfs64>::uninit();
+        unsafe {
+            try_libc!(libc::fstatfs64(fd.as_raw_fd(), statfs.as_mut_ptr()))?;
+            Ok(statfs.assume_init().f_type as u32 == HUGETLBFS_MAGIC)
+        }
+    }
+
+    // Grow `size` to cover an existing object that another process created larger,
+    // so that mapping it doesn't leave the remainder inaccessible.
+    pub(crate) fn with_existing_size(mut self) -> crate::Result<Self> {
+        if self.create {
+            return Ok(self);
+        }
+
+        if let Some(stat) = self.stat()? {
+            let actual = (stat.st_size as usize).next_multiple_of(Page::SIZE);
+            self.size = self
+                .size
+                .max(NonZeroUsize::new(actual).unwrap_or(self.size));
+        }
+
+        Ok(self)
+    }
+
+    /// Fail with `Error::SizeMismatch` if the backing file is smaller than `expected`.
+    /// Anonymous mappings are always the requested size.
+    pub(crate) fn validate_size(&self, expected: usize) -> crate::Result<()> {
+        match self.stat()? {
+            Some(stat) if (stat.st_size as usize) < expected => Err(crate::Error::SizeMismatch {
+                expected,
+                actual: stat.st_size as usize,
+            }),
+            Some(_) | None => Ok(()),
+        }
+    }
+
     pub(crate) fn flags(&self) -> libc::c_int {
         match self.fd {
-            Some(_) => libc::MAP_SHARED_VALIDATE,
+            Some(_) if SHARED_VALIDATE.load(Ordering::Relaxed) => libc::MAP_SHARED_VALIDATE,
+            Some(_) => libc::MAP_SHARED,
             None => libc::MAP_ANONYMOUS | libc::MAP_PRIVATE,
         }
     }
 }
 
-#[bon::bon]
 impl File {
-    #[builder]
     pub(crate) fn new(fd: Option<OwnedFd>, size: NonZeroUsize, offset: i64, create: bool) -> Self {
         Self {
             fd,
             size,
             offset,
             create,
+            protection: Protection::default(),
         }
     }
+}
 
-    /// SAFETY: caller must ensure `address` does not overlap an existing memory region.
+/// Options for [`File::map_with`], which mirror the `File::map` builder.
+pub struct MapOptions {
+    pub address: Option<NonNull<Page>>,
+    /// With `address`, use `MAP_FIXED_NOREPLACE` instead of `MAP_FIXED`.
+    pub fixed_no_replace: bool,
+    /// Preferred address, which the kernel may ignore. Ignored if `address` is set.
+    pub hint: Option<NonNull<Page>>,
+    pub numa: Option<Numa>,
+    pub mbind_flags: MbindFlags,
+    pub populate: Option<Populate>,
+    /// Defaults to the access the file was opened with.
+    pub protection: Option<Protection>,
+    /// Passing `false` maps with `MAP_NORESERVE`. Defaults to `true`.
+    pub reserve: bool,
+    /// Map copy-on-write with `MAP_PRIVATE`.
+    pub private: bool,
+    pub huge_page: HugePage,
+}
+
+impl Default for MapOptions {
+    fn default() -> Self {
+        Self {
+            address: None,
+            fixed_no_replace: false,
+            hint: None,
+            numa: None,
+            mbind_flags: MbindFlags::default(),
+            populate: None,
+            protection: None,
+            reserve: true,
+            private: false,
+            huge_page: HugePage::default(),
+        }
+    }
+}
+
+#[cfg(feature = "builder")]
+#[bon::bon]
+impl File {
+    /// SAFETY: see [`File::map_with`].
+    ///
+    /// `protection` defaults to the access the file was opened with.
     #[builder]
     pub unsafe fn map(
-        self,
+        &self,
         address: Option<NonNull<Page>>,
+        /// With `address`, use `MAP_FIXED_NOREPLACE` instead of `MAP_FIXED`, failing
+        /// with `EEXIST` instead of replacing any existing mapping in the range.
+        #[builder(default)]
+        fixed_no_replace: bool,
+        /// Preferred address, which the kernel may ignore if it is taken.
+        /// Unlike `address`, this does not use `MAP_FIXED`. Ignored if `address` is set.
+        hint: Option<NonNull<Page>>,
         numa: Option<Numa>,
+        /// Flags for applying `numa`. See [`MbindFlags`].
+        #[builder(default)]
+        mbind_flags: MbindFlags,
         populate: Option<Populate>,
+        protection: Option<Protection>,
+        /// Passing `false` maps with `MAP_NORESERVE`, so no swap space is reserved up
+        /// front, e.g. for large sparse segments on systems with strict overcommit.
+        /// Writes may then raise `SIGSEGV` if memory runs out.
+        #[builder(default = true)]
+        reserve: bool,
+        /// Map copy-on-write with `MAP_PRIVATE`, so writes are not shared.
+        #[builder(default)]
+        private: bool,
+        /// Only requested via `MAP_HUGETLB` for anonymous mappings, since the kernel
+        /// rejects it for files, which must instead live on hugetlbfs. Other files
+        /// fail with `Error::HugePageUnsupported`.
+        #[builder(default)]
+        huge_page: HugePage,
     ) -> crate::Result<NonNull<Page>> {
-        let actual = unsafe {
+        unsafe {
+            self.map_with(MapOptions {
+                address,
+                fixed_no_replace,
+                hint,
+                numa,
+                mbind_flags,
+                populate,
+                protection,
+                reserve,
+                private,
+                huge_page,
+            })
+        }
+    }
+}
+
+impl File {
+    /// Like the `File::map` builder, e.g. without the `builder` feature.
+    ///
+    /// SAFETY: caller must ensure `address` does not overlap an existing memory region.
+    pub unsafe fn map_with(&self, options: MapOptions) -> crate::Result<NonNull<Page>> {
+        let MapOptions {
+            address,
+            fixed_no_replace,
+            hint,
+            numa,
+            mbind_flags,
+            populate,
+            protection,
+            reserve,
+            private,
+            huge_page,
+        } = options;
+
+        // The page size of file-backed mappings is determined by the filesystem,
+        // so rather than silently mapping base pages, require hugetlbfs.
+        if huge_page != HugePage::None && self.fd.is_some() && !self.is_hugetlbfs()? {
+            return Err(crate::Error::HugePageUnsupported {
+                size: huge_page.size(),
+            });
+        }
+
+        let fixed = match fixed_no_replace {
+            true => libc::MAP_FIXED_NOREPLACE,
+            false => libc::MAP_FIXED,
+        };
+
+        let extra = address.map(|_| fixed).unwrap_or(0)
+            | if reserve { 0 } else { libc::MAP_NORESERVE }
+            | if matches!(populate, Some(Populate::PageTable)) {
+                libc::MAP_POPULATE
+            } else {
+                0
+            };
+
+        let mmap = |flags: libc::c_int| unsafe {
             try_libc!(libc::mmap64(
                 address
+                    .or(hint)
                     .map(NonNull::as_ptr)
                     .unwrap_or_else(ptr::null_mut)
                     .cast(),
                 self.size.get(),
-                libc::PROT_READ | libc::PROT_WRITE,
-                self.flags()
-                    | address.map(|_| libc::MAP_FIXED).unwrap_or(0)
-                    | if matches!(populate, Some(Populate::PageTable)) {
-                        libc::MAP_POPULATE
-                    } else {
-                        0
-                    },
+                protection.unwrap_or(self.protection).prot(),
+                flags | extra,
                 self.as_raw_fd(),
                 self.offset,
             ))
+        };
+
+        let flags = match private {
+            true => (self.flags() & !libc::MAP_SHARED_VALIDATE) | libc::MAP_PRIVATE,
+            false => self.flags(),
+        } | match self.fd {
+            Some(_) => 0,
+            None => huge_page.flags(),
+        };
+
+        let actual = match mmap(flags) {
+            // Kernels before 4.15 reject `MAP_SHARED_VALIDATE`, which is equivalent
+            // to `MAP_SHARED` unless combined with flags like `MAP_SYNC`.
+            Err(crate::Error::Libc { source, .. })
+                if flags == libc::MAP_SHARED_VALIDATE
+                    && matches!(source.raw_os_error(), Some(libc::EINVAL | libc::EOPNOTSUPP)) =>
+            {
+                let actual = mmap(libc::MAP_SHARED)?;
+                log::info!("MAP_SHARED_VALIDATE unsupported, falling back to MAP_SHARED");
+                SHARED_VALIDATE.store(false, Ordering::Relaxed);
+                Ok(actual)
+            }
+            Err(crate::Error::Libc { source, .. })
+                if huge_page != HugePage::None && source.raw_os_error() == Some(libc::ENOMEM) =>
+            {
+                Err(crate::Error::HugePageUnavailable {
+                    size: huge_page.size(),
+                    source,
+                })
+            }
+            result => result,
         }
         .map(NonNull::new)
         .map(Option::unwrap)
         .map(|address| address.cast::<Page>())?;
 
-        if let Some(expected) = address {
-            assert_eq!(expected, actual);
+        match address {
+            // Kernels before 4.17 treat `MAP_FIXED_NOREPLACE` as a hint.
+            Some(expected) if fixed_no_replace && expected != actual => {
+                unsafe { try_libc!(libc::munmap(actual.as_ptr().cast(), self.size.get())) }?;
+                return Err(crate::Error::Libc {
+                    name: "mmap64",
+                    source: std::io::Error::from_raw_os_error(libc::EEXIST),
+                });
+            }
+            Some(expected) => assert_eq!(expected, actual),
+            None => (),
         }
 
-        if let Some(numa) = numa {
-            numa.mbind(actual.as_ptr().cast(), self.size.get())?;
-        }
+        let setup = || -> crate::Result<()> {
+            if let Some(numa) = numa {
+                numa.mbind(actual.as_ptr().cast(), self.size.get(), mbind_flags)?;
+            }
+
+            // Best-effort, since kernels without transparent huge pages reject the advice.
+            if self.size.get() >= AUTO_THP_THRESHOLD.load(Ordering::Relaxed) {
+                if let Err(error) =
+                    madvise(actual.as_ptr().cast(), self.size.get(), libc::MADV_HUGEPAGE)
+                {
+                    log::warn!("Failed to advise transparent huge pages: {}", error);
+                }
+            }
+
+            if let Some(Populate::Prefix { pages }) = populate {
+                let size = self.size.get().min(pages.saturating_mul(Page::SIZE));
+                if size > 0 {
+                    madvise(actual.as_ptr().cast(), size, libc::MADV_WILLNEED)?;
+                }
+            }
+
+            if matches!(populate, Some(Populate::Physical)) {
+                crate::prefault::populate(actual.as_ptr().cast(), self.size.get())?;
+            }
+
+            if matches!(populate, Some(Populate::Lock)) {
+                mlock(actual.as_ptr().cast(), self.size.get())?;
+            }
 
-        if matches!(populate, Some(Populate::Physical)) {
-            madvise(actual.as_ptr().cast(), self.size.get())?;
+            Ok(())
+        };
+
+        if let Err(error) = setup() {
+            unsafe { self.unmap(actual, address.is_some() && !fixed_no_replace) };
+            return Err(error);
         }
 
         Ok(actual)
     }
+
+    // Undo a mapping at `address` after a failure. Mappings made with `MAP_FIXED`
+    // replaced whatever was mapped before, e.g. part of a `Reservation`, so they are
+    // replaced with an inaccessible placeholder instead of leaving a hole.
+    unsafe fn unmap(&self, address: NonNull<Page>, replaced: bool) {
+        let address = address.as_ptr().cast();
+        match replaced {
+            true => unsafe {
+                libc::mmap64(
+                    address,
+                    self.size.get(),
+                    libc::PROT_NONE,
+                    libc::MAP_ANONYMOUS | libc::MAP_PRIVATE | libc::MAP_NORESERVE | libc::MAP_FIXED,
+                    -1,
+                    0,
+                );
+            },
+            false => unsafe {
+                libc::munmap(address, self.size.get());
+            },
+        }
+    }
+}
+
+// Cleared after the first mapping that fails because `MAP_SHARED_VALIDATE` is unsupported.
+static SHARED_VALIDATE: AtomicBool = AtomicBool::new(true);
+
+static AUTO_THP_THRESHOLD: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Advise the kernel to back mappings of at least `bytes` with transparent
+/// huge pages (`MADV_HUGEPAGE`). Smaller mappings are left alone to avoid
+/// wasting huge pages. Disabled by default.
+///
+/// Note: shared memory mappings also require `shmem_enabled` to be set to
+/// `advise` in `/sys/kernel/mm/transparent_hugepage/`.
+pub fn set_auto_thp_threshold(bytes: usize) {
+    AUTO_THP_THRESHOLD.store(bytes, Ordering::Relaxed);
 }
 
 // SAFETY: `libc::madvise` will not dereference invalid address.
 #[expect(clippy::not_unsafe_ptr_arg_deref)]
-fn madvise(address: *mut ffi::c_void, size: usize) -> crate::Result<()> {
-    unsafe { try_libc!(libc::madvise(address, size, libc::MADV_POPULATE_WRITE)) }?;
+pub(crate) fn madvise(
+    address: *mut ffi::c_void,
+    size: usize,
+    advice: libc::c_int,
+) -> crate::Result<()> {
+    unsafe { try_libc!(libc::madvise(address, size, advice)) }?;
     Ok(())
 }
+
+// SAFETY: `libc::mlock2` will not dereference invalid address.
+#[expect(clippy::not_unsafe_ptr_arg_deref)]
+pub(crate) fn mlock(address: *mut ffi::c_void, size: usize) -> crate::Result<()> {
+    match unsafe { try_libc!(libc::mlock2(address, size, 0)) } {
+        Ok(_) => Ok(()),
+        // `EPERM` without `CAP_IPC_LOCK` and a zero limit, `ENOMEM` when over the limit.
+        Err(crate::Error::Libc { source, .. })
+            if matches!(source.raw_os_error(), Some(libc::EPERM | libc::ENOMEM)) =>
+        {
+            Err(crate::Error::MemlockLimit { size, source })
+        }
+        Err(error) => Err(error),
+    }
+}
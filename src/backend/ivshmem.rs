@@ -1,23 +1,37 @@
 use core::num::NonZeroUsize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::fs::File;
+use std::io;
 use std::os::fd::OwnedFd;
 use std::path::Path;
+use std::sync::Mutex;
 
 use crate::backend::Backend;
+use crate::hash::Fx;
 
 #[derive(Debug)]
 pub struct Ivshmem {
     device: File,
+    // Original ID of each hashed ID, to detect collisions.
+    hashed: Option<Mutex<HashMap<String, String>>>,
 }
 
 impl Ivshmem {
+    /// Longest ID that fits in `prog_id` with its null terminator.
+    const MAX_ID_LEN: usize = 27;
+
+    // Leaves room for a separator and 16 hex digits of hash.
+    const HASHED_PREFIX_LEN: usize = Self::MAX_ID_LEN - 17;
+
     #[allow(clippy::new_without_default)]
     pub fn new() -> std::io::Result<Self> {
         File::options()
             .read(true)
             .write(true)
             .open("/dev/cxl_ivpci0")
-            .map(|device| Self { device })
+            .map(Self::with_device)
     }
 
     pub fn open(path: &Path) -> std::io::Result<Self> {
@@ -25,7 +39,56 @@ impl Ivshmem {
             .read(true)
             .write(true)
             .open(path)
-            .map(|device| Self { device })
+            .map(Self::with_device)
+    }
+
+    /// Hash IDs too long for the driver's `prog_id` field instead of panicking.
+    /// Hashing is deterministic, so processes opening the same long ID share the
+    /// same allocation. IDs that already fit are passed through unchanged.
+    pub fn hash_long_ids(mut self) -> Self {
+        self.hashed = Some(Mutex::default());
+        self
+    }
+
+    fn with_device(device: File) -> Self {
+        Self {
+            device,
+            hashed: None,
+        }
+    }
+
+    fn id<'id>(&self, id: &'id str) -> Cow<'id, str> {
+        let Some(hashed) = &self.hashed else {
+            return Cow::Borrowed(id);
+        };
+
+        if id.len() <= Self::MAX_ID_LEN {
+            return Cow::Borrowed(id);
+        }
+
+        // Keep a readable prefix, cut at a character boundary.
+        let mut end = Self::HASHED_PREFIX_LEN;
+        while !id.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let hash = format!("{}~{:016x}", &id[..end], Fx::hash(id.as_bytes()));
+        match hashed.lock().unwrap().entry(hash.clone()) {
+            Entry::Occupied(entry) if entry.get() != id => {
+                log::warn!(
+                    "Ivshmem ID hash collision: {:?} and {:?} both hash to {:?}",
+                    entry.get(),
+                    id,
+                    hash,
+                );
+            }
+            Entry::Occupied(_) => (),
+            Entry::Vacant(entry) => {
+                entry.insert(id.to_owned());
+            }
+        }
+
+        Cow::Owned(hash)
     }
 }
 
@@ -35,24 +98,64 @@ impl crate::backend::Interface for Ivshmem {
     }
 
     fn open(&self, id: &str, size: NonZeroUsize) -> crate::Result<super::File> {
-        let allocation = driver::find_cxl_alloc_nomap(&self.device, &id, size.get())
-            .expect("Failed to allocate from ivshmem device");
+        let allocation = driver::find_cxl_alloc_nomap(&self.device, &self.id(id), size.get())
+            .map_err(|source| crate::Error::Libc {
+                name: "ioctl",
+                source,
+            })?;
+
+        let device = self
+            .device
+            .try_clone()
+            .map_err(|source| crate::Error::Libc {
+                name: "fcntl",
+                source,
+            })?;
 
-        Ok(crate::backend::File::builder()
-            .fd(OwnedFd::from(
-                self.device
-                    .try_clone()
-                    .expect("Failed to clone ivshmem device"),
-            ))
-            .size(size)
-            .offset(allocation.desc.offset as i64)
-            .create(allocation.existing == 0)
-            .build())
+        Ok(crate::backend::File::new(
+            Some(OwnedFd::from(device)),
+            size,
+            allocation.desc.offset as i64,
+            allocation.existing == 0,
+        ))
     }
 
-    fn unlink(&self, _id: &str) -> crate::Result<()> {
-        // FIXME: call `driver::cxl_free`
-        Ok(())
+    /// Unsupported: the driver has no lookup that doesn't allocate, and freeing
+    /// an allocation made by mistake could race with a creator adopting it.
+    fn attach(&self, _: &str, _: NonZeroUsize) -> crate::Result<super::File> {
+        Err(crate::Error::Libc {
+            name: "ioctl",
+            source: io::Error::from(io::ErrorKind::Unsupported),
+        })
+    }
+
+    /// Free the allocation on the device.
+    ///
+    /// The driver requires the offset and size of the allocation to free it, which
+    /// are looked up with a zero-length `find_cxl_alloc_nomap` so that unknown IDs
+    /// are not allocated. Unknown IDs fail with `ENOENT`.
+    fn unlink(&self, id: &str) -> crate::Result<()> {
+        let id = self.id(id);
+        let map_err = |source| crate::Error::Libc {
+            name: "ioctl",
+            source,
+        };
+
+        let allocation = driver::find_cxl_alloc_nomap(&self.device, &id, 0).map_err(map_err)?;
+        if allocation.existing == 0 {
+            return Err(crate::Error::Libc {
+                name: "ioctl",
+                source: io::Error::from_raw_os_error(libc::ENOENT),
+            });
+        }
+
+        driver::cxl_free(
+            &self.device,
+            &id,
+            allocation.desc.offset as i64,
+            allocation.desc.length as usize,
+        )
+        .map_err(map_err)
     }
 }
 
@@ -82,6 +185,9 @@ mod driver {
         dir: Dir,
     }
 
+    // Direction of the data transfer from the caller's point of view, so `W`
+    // (`_IOW`) means the kernel only reads the argument, and `R` (`_IOR`)
+    // means the kernel only writes it.
     #[ribbit::pack(size = 2, debug)]
     enum Dir {
         None,
@@ -149,7 +255,7 @@ mod driver {
             7,
             IOCTL_MAGIC,
             u14::new(size_of::<region_desc>() as u16),
-            // Possibly an issue with the driver interface? Should at least be `R`.
+            // The driver only reads the descriptor to find the allocation to free.
             Dir::new(DirUnpacked::W),
         );
 
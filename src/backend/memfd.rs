@@ -0,0 +1,80 @@
+use core::num::NonZeroUsize;
+use std::ffi::CString;
+use std::io;
+use std::os::fd::AsRawFd as _;
+use std::os::fd::BorrowedFd;
+use std::os::fd::FromRawFd as _;
+use std::os::fd::OwnedFd;
+
+use crate::Page;
+use crate::backend;
+
+/// Anonymous files created with `memfd_create`, which are not visible in any
+/// filesystem. Other processes attach by receiving the file descriptor, e.g.
+/// over a Unix domain socket, and mapping it with [`Raw::from_owned_fd`](crate::Raw::from_owned_fd).
+#[derive(Clone, Debug, Default)]
+pub struct Memfd;
+
+impl backend::Interface for Memfd {
+    fn name(&self) -> &'static str {
+        "memfd"
+    }
+
+    /// `id` is only used for debugging, e.g. in `/proc/self/fd`.
+    fn open(&self, id: &str, size: NonZeroUsize) -> crate::Result<backend::File> {
+        let name = CString::new(id).map_err(|_| crate::Error::ShmName)?;
+        let size = size.get().next_multiple_of(Page::SIZE);
+
+        let fd = unsafe {
+            crate::try_libc!(libc::memfd_create(
+                name.as_ptr(),
+                libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING,
+            ))
+            .map(|fd| OwnedFd::from_raw_fd(fd))?
+        };
+
+        unsafe {
+            crate::try_libc!(libc::ftruncate64(fd.as_raw_fd(), size as i64))?;
+        }
+
+        Ok(backend::File::new(
+            Some(fd),
+            NonZeroUsize::new(size).unwrap(),
+            0,
+            true,
+        ))
+    }
+
+    /// Other processes can only attach by receiving the file descriptor.
+    fn attach(&self, _: &str, _: NonZeroUsize) -> crate::Result<backend::File> {
+        Err(crate::Error::Libc {
+            name: "memfd_create",
+            source: io::Error::from_raw_os_error(libc::ENOENT),
+        })
+    }
+
+    fn unlink(&self, _id: &str) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+impl Memfd {
+    /// Prevent the size of the memfd from changing and any further seals from being
+    /// added, so processes receiving the file descriptor can trust its size.
+    pub fn seal(fd: BorrowedFd<'_>) -> crate::Result<()> {
+        unsafe {
+            crate::try_libc!(libc::fcntl(
+                fd.as_raw_fd(),
+                libc::F_ADD_SEALS,
+                libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_SEAL,
+            ))
+        }?;
+        Ok(())
+    }
+}
+
+impl From<Memfd> for backend::Backend {
+    fn from(memfd: Memfd) -> Self {
+        backend::Backend::Memfd(memfd)
+    }
+}
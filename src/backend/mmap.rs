@@ -1,4 +1,5 @@
 use core::num::NonZeroUsize;
+use std::io;
 
 use crate::Page;
 use crate::backend;
@@ -13,11 +14,14 @@ impl backend::Interface for Mmap {
 
     fn open(&self, _: &str, size: NonZeroUsize) -> crate::Result<backend::File> {
         let size = NonZeroUsize::new(size.get().next_multiple_of(Page::SIZE)).unwrap();
-        Ok(backend::File::builder()
-            .size(size)
-            .offset(0)
-            .create(true)
-            .build())
+        Ok(backend::File::new(None, size, 0, true))
+    }
+
+    fn attach(&self, _: &str, _: NonZeroUsize) -> crate::Result<backend::File> {
+        Err(crate::Error::Libc {
+            name: "mmap64",
+            source: io::Error::from_raw_os_error(libc::ENOENT),
+        })
     }
 
     fn unlink(&self, _id: &str) -> crate::Result<()> {
@@ -0,0 +1,108 @@
+use core::num::NonZeroUsize;
+use std::fs::OpenOptions;
+use std::io;
+use std::os::fd::OwnedFd;
+use std::os::unix::fs::OpenOptionsExt as _;
+use std::path::PathBuf;
+
+use crate::Page;
+use crate::backend;
+
+/// Regular files under an arbitrary path, e.g. on a hugetlbfs mount or a
+/// DAX filesystem backed by persistent memory, which `shm_open` can't reach.
+#[derive(Clone, Debug)]
+pub struct FileBacked {
+    path: PathBuf,
+}
+
+impl FileBacked {
+    /// If `path` is a directory, segments are files named by their id within it.
+    /// Otherwise, every segment maps the file at `path` itself.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn resolve(&self, id: &str) -> PathBuf {
+        match self.path.is_dir() {
+            true => self.path.join(id),
+            false => self.path.clone(),
+        }
+    }
+}
+
+impl backend::Interface for FileBacked {
+    fn name(&self) -> &'static str {
+        "path"
+    }
+
+    fn open(&self, id: &str, size: NonZeroUsize) -> crate::Result<backend::File> {
+        let path = self.resolve(id);
+        let size = size.get().next_multiple_of(Page::SIZE);
+        let open = |create: bool| {
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(create)
+                .mode(0o666)
+                .open(&path)
+        };
+
+        let (create, file) = match open(true) {
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => (false, open(false)),
+            result => (true, result),
+        };
+
+        let file = file.map_err(|source| crate::Error::Libc {
+            name: "open",
+            source,
+        })?;
+
+        if create {
+            file.set_len(size as u64)
+                .map_err(|source| crate::Error::Libc {
+                    name: "ftruncate",
+                    source,
+                })?;
+        }
+
+        backend::File::new(
+            Some(OwnedFd::from(file)),
+            NonZeroUsize::new(size).unwrap(),
+            0,
+            create,
+        )
+        .with_existing_size()
+    }
+
+    fn attach(&self, id: &str, size: NonZeroUsize) -> crate::Result<backend::File> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(self.resolve(id))
+            .map_err(|source| crate::Error::Libc {
+                name: "open",
+                source,
+            })?;
+
+        backend::File::new(
+            Some(OwnedFd::from(file)),
+            NonZeroUsize::new(size.get().next_multiple_of(Page::SIZE)).unwrap(),
+            0,
+            false,
+        )
+        .with_existing_size()
+    }
+
+    fn unlink(&self, id: &str) -> crate::Result<()> {
+        std::fs::remove_file(self.resolve(id)).map_err(|source| crate::Error::Libc {
+            name: "unlink",
+            source,
+        })
+    }
+}
+
+impl From<FileBacked> for backend::Backend {
+    fn from(path: FileBacked) -> Self {
+        backend::Backend::Path(path)
+    }
+}
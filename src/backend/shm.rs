@@ -1,15 +1,26 @@
 use core::ffi::CStr;
+use core::fmt;
 use core::num::NonZeroUsize;
+use core::ops::Deref;
 
 use std::os::fd::AsRawFd;
 use std::os::fd::FromRawFd as _;
 use std::os::fd::OwnedFd;
 
 use crate::Page;
+use crate::Protection;
 use crate::backend;
 
 #[derive(Debug)]
-pub struct Shm;
+pub struct Shm {
+    mode: libc::mode_t,
+}
+
+impl Default for Shm {
+    fn default() -> Self {
+        Self { mode: 0o666 }
+    }
+}
 
 impl backend::Interface for Shm {
     fn name(&self) -> &'static str {
@@ -17,40 +28,11 @@ impl backend::Interface for Shm {
     }
 
     fn open(&self, id: &str, size: NonZeroUsize) -> crate::Result<backend::File> {
-        let size = size.get().next_multiple_of(Page::SIZE);
-
-        let (create, fd) = Self::with_path(id, |path| {
-            let (create, fd) = match unsafe {
-                crate::try_libc!(libc::shm_open(
-                    path.as_ptr(),
-                    libc::O_CREAT | libc::O_EXCL | libc::O_RDWR,
-                    0o666,
-                ))
-            } {
-                Err(error) if error.is_already_exists() => unsafe {
-                    let fd = crate::try_libc!(libc::shm_open(path.as_ptr(), libc::O_RDWR, 0o666))
-                        .map(|fd| OwnedFd::from_raw_fd(fd))?;
-                    Ok((false, fd))
-                },
-                Err(error) => Err(error),
-                Ok(fd) => Ok((true, unsafe { OwnedFd::from_raw_fd(fd) })),
-            }?;
-
-            if create {
-                unsafe {
-                    crate::try_libc!(libc::ftruncate64(fd.as_raw_fd(), size as i64))?;
-                }
-            }
-
-            Ok((create, fd))
-        })?;
+        Self::with_path(id, |path| self.open_cstr(path, size))
+    }
 
-        Ok(backend::File::builder()
-            .fd(fd)
-            .size(NonZeroUsize::new(size).unwrap())
-            .create(create)
-            .offset(0)
-            .build())
+    fn attach(&self, id: &str, size: NonZeroUsize) -> crate::Result<backend::File> {
+        Self::with_path(id, |path| Self::attach_cstr(path, size))
     }
 
     fn unlink(&self, id: &str) -> crate::Result<()> {
@@ -64,20 +46,174 @@ impl From<Shm> for backend::Backend {
     }
 }
 
-pub type Path = [u8; Shm::MAX_LEN + 1];
+/// NUL-terminated shared memory object path, stored inline so that building
+/// one never allocates.
+#[derive(Copy, Clone)]
+pub struct Path([u8; Shm::MAX_LEN + 2]);
+
+impl Deref for Path {
+    type Target = CStr;
+
+    fn deref(&self) -> &Self::Target {
+        CStr::from_bytes_until_nul(&self.0).unwrap()
+    }
+}
+
+impl fmt::Debug for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_string_lossy())
+    }
+}
 
 impl Shm {
     pub const MAX_LEN: usize = 62;
 
-    fn with_path<T, F: FnOnce(&CStr) -> crate::Result<T>>(id: &str, apply: F) -> crate::Result<T> {
-        if id.len() > Self::MAX_LEN {
+    /// POSIX shared memory object names must start with a single `/`.
+    const PREFIX: &str = "/";
+
+    /// Create shared memory objects with permission bits `mode` instead of `0o666`,
+    /// e.g. `0o600` for segments holding sensitive data on multi-user hosts.
+    ///
+    /// Only applies when creating an object, and is masked by the process umask.
+    pub fn with_mode(mode: libc::mode_t) -> Self {
+        Self { mode }
+    }
+
+    /// Open the shared memory object at `path`, which must already include the leading `/`.
+    ///
+    /// Unlike [`Backend::open`](crate::Backend::open), this skips copying the name into
+    /// a NUL-terminated buffer when the caller already holds a `CStr`.
+    pub fn open_path(&self, path: &CStr, size: NonZeroUsize) -> crate::Result<backend::File> {
+        Self::with_cstr(path, |path| self.open_cstr(path, size))
+    }
+
+    /// Unlink the shared memory object at `path`, which must already include the leading `/`.
+    pub fn unlink_path(&self, path: &CStr) -> crate::Result<()> {
+        Self::with_cstr(path, shm_unlink)
+    }
+
+    /// Open an existing shared memory object without write access,
+    /// checking that it is at least `size` bytes.
+    pub fn open_readonly(&self, id: &str, size: NonZeroUsize) -> crate::Result<backend::File> {
+        let size = size.get().next_multiple_of(Page::SIZE);
+
+        let fd = Self::with_path(id, |path| unsafe {
+            crate::try_libc!(libc::shm_open(
+                path.as_ptr(),
+                libc::O_RDONLY | libc::O_CLOEXEC,
+                0
+            ))
+            .map(|fd| OwnedFd::from_raw_fd(fd))
+        })?;
+
+        let mut file = backend::File::new(Some(fd), NonZeroUsize::new(size).unwrap(), 0, false);
+        file.set_protection(Protection::ReadOnly);
+
+        file.validate_size(size)?;
+        Ok(file)
+    }
+
+    fn open_cstr(&self, path: &CStr, size: NonZeroUsize) -> crate::Result<backend::File> {
+        let fd = match unsafe {
+            crate::try_libc!(libc::shm_open(
+                path.as_ptr(),
+                libc::O_CREAT | libc::O_EXCL | libc::O_RDWR | libc::O_CLOEXEC,
+                self.mode,
+            ))
+        } {
+            Err(error) if error.is_already_exists() => return Self::attach_cstr(path, size),
+            Err(error) => return Err(error),
+            Ok(fd) => unsafe { OwnedFd::from_raw_fd(fd) },
+        };
+
+        let size = size.get().next_multiple_of(Page::SIZE);
+        unsafe {
+            crate::try_libc!(libc::ftruncate64(fd.as_raw_fd(), size as i64))?;
+        }
+
+        Ok(backend::File::new(
+            Some(fd),
+            NonZeroUsize::new(size).unwrap(),
+            0,
+            true,
+        ))
+    }
+
+    // Never passes `O_CREAT`, so a missing object fails with `ENOENT`.
+    fn attach_cstr(path: &CStr, size: NonZeroUsize) -> crate::Result<backend::File> {
+        let size = size.get().next_multiple_of(Page::SIZE);
+        let mut protection = Protection::ReadWrite;
+
+        let fd = unsafe {
+            match crate::try_libc!(libc::shm_open(
+                path.as_ptr(),
+                libc::O_RDWR | libc::O_CLOEXEC,
+                0
+            )) {
+                // Fall back to read-only access for least-privilege consumers.
+                Err(error) if error.is_permission_denied() => {
+                    protection = Protection::ReadOnly;
+                    crate::try_libc!(libc::shm_open(
+                        path.as_ptr(),
+                        libc::O_RDONLY | libc::O_CLOEXEC,
+                        0
+                    ))
+                }
+                result => result,
+            }
+            .map(|fd| OwnedFd::from_raw_fd(fd))?
+        };
+
+        let mut file = backend::File::new(Some(fd), NonZeroUsize::new(size).unwrap(), 0, false);
+        file.set_protection(protection);
+        file.with_existing_size()
+    }
+
+    /// Build a NUL-terminated `/` + `id` path in a fixed stack buffer, and attach
+    /// it to any error returned by `apply`. Shared with [`crate::Semaphore`], whose
+    /// names follow the same convention.
+    pub(crate) fn with_path<T, F: FnOnce(&CStr) -> crate::Result<T>>(
+        id: &str,
+        apply: F,
+    ) -> crate::Result<T> {
+        let path = Self::path(&[Self::PREFIX.as_bytes(), id.as_bytes()])?;
+        apply(&path).map_err(|error| error.with_path(path))
+    }
+
+    fn with_cstr<T, F: FnOnce(&CStr) -> crate::Result<T>>(
+        path: &CStr,
+        apply: F,
+    ) -> crate::Result<T> {
+        let bytes = path.to_bytes();
+        if !bytes.starts_with(Self::PREFIX.as_bytes()) || bytes.len() > Self::MAX_LEN + 1 {
             return Err(crate::Error::ShmName);
         }
 
-        let mut path = [0u8; Self::MAX_LEN + 1];
-        path[0] = b'/';
-        path[1..][..id.len()].copy_from_slice(id.as_bytes());
-        apply(CStr::from_bytes_until_nul(&path).unwrap()).map_err(|error| error.with_path(path))
+        // Only copy into a buffer on the error path. Length is checked above.
+        apply(path).map_err(|error| error.with_path(Self::path(&[bytes]).unwrap()))
+    }
+
+    fn path(parts: &[&[u8]]) -> crate::Result<Path> {
+        let mut path = [0u8; Self::MAX_LEN + 2];
+        let mut len = 0;
+
+        for part in parts {
+            // Leave room for NUL terminator.
+            if len + part.len() > Self::MAX_LEN + 1 {
+                return Err(crate::Error::ShmName);
+            }
+
+            path[len..][..part.len()].copy_from_slice(part);
+            len += part.len();
+        }
+
+        Ok(Path(path))
     }
 }
 
@@ -1,59 +1,221 @@
 use core::mem::MaybeUninit;
+use core::sync::atomic::AtomicI32;
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
+use core::time::Duration;
+use std::io;
 
-use bon::bon;
-
+use crate::Clock;
+#[cfg(feature = "builder")]
+use crate::Numa;
+#[cfg(feature = "builder")]
+use crate::Populate;
+use crate::SegmentRole;
 use crate::Shm;
 use crate::try_pthread;
 
-pub struct Barrier(Shm<libc::pthread_barrier_t>);
+pub struct Barrier {
+    inner: Shm<Inner>,
+    slot: Option<usize>,
+}
+
+/// Outcome of [`Barrier::wait_role`] for the calling thread.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Exactly one thread per phase is designated the serial thread,
+    /// e.g. to perform work on behalf of all participants.
+    Serial,
+    Member,
+}
+
+#[repr(C)]
+struct Inner {
+    barrier: libc::pthread_barrier_t,
+    // PIDs of registered participants, or zero for free slots.
+    pids: [AtomicI32; Barrier::MAX_PARTICIPANTS],
+    // Number of threads currently blocked in `wait_role`.
+    arrived: AtomicU32,
+    thread_count: u32,
+    // Phase number in the high half and number of threads that have arrived
+    // in the phase in the low half, packed so that timed-out waiters can
+    // withdraw atomically unless the phase already completed.
+    phase: AtomicU64,
+}
 
 unsafe impl Sync for Barrier {}
 unsafe impl Send for Barrier {}
 
-#[bon]
+#[cfg(feature = "builder")]
+#[bon::bon]
 impl Barrier {
     #[builder]
     pub fn new(
         name: String,
         #[builder(default)] create: bool,
         thread_count: u32,
+        /// Record this process's PID in the barrier so that
+        /// [`Barrier::check_participants`] can detect if it dies.
+        #[builder(default)]
+        register: bool,
+        /// Placement of the barrier's page, which is hot during `wait`.
+        numa: Option<Numa>,
+        populate: Option<Populate>,
+    ) -> crate::Result<Self> {
+        Self::with_options(
+            name,
+            thread_count,
+            register,
+            crate::raw::Options {
+                create,
+                numa,
+                populate,
+                ..crate::raw::Options::default()
+            },
+        )
+    }
+}
+
+impl Barrier {
+    /// Maximum number of participants that can be tracked by [`Barrier::check_participants`].
+    pub const MAX_PARTICIPANTS: usize = 64;
+
+    // How often `wait_timeout` checks whether the other participants have arrived.
+    const POLL_INTERVAL: Duration = Duration::from_micros(100);
+
+    /// Like `Barrier::builder()` with only these options, so this process is not
+    /// registered as a participant.
+    pub fn named(name: String, thread_count: u32, create: bool) -> crate::Result<Self> {
+        Self::with_options(
+            name,
+            thread_count,
+            false,
+            crate::raw::Options {
+                create,
+                ..crate::raw::Options::default()
+            },
+        )
+    }
+
+    fn with_options(
+        name: String,
+        thread_count: u32,
+        register: bool,
+        options: crate::raw::Options,
     ) -> crate::Result<Self> {
-        let inner = Shm::<libc::pthread_barrier_t>::builder()
-            .name(name)
-            .create(create)
-            .build()?;
+        let create = options.create;
+        let inner = Shm::<Inner>::with_options(name, options)?;
 
         if create {
-            let mut attr = unsafe {
-                let mut attr = MaybeUninit::<libc::pthread_barrierattr_t>::zeroed();
-                try_pthread!(libc::pthread_barrierattr_init(attr.as_mut_ptr()))?;
-                try_pthread!(libc::pthread_barrierattr_setpshared(
-                    attr.as_mut_ptr(),
-                    libc::PTHREAD_PROCESS_SHARED
-                ))?;
-                attr.assume_init()
-            };
-
-            unsafe {
-                try_pthread!(libc::pthread_barrier_init(
-                    inner.address().as_ptr(),
-                    &attr,
-                    thread_count
-                ))?;
-            }
+            Self::init(&inner, thread_count)?;
+        }
 
-            unsafe {
-                assert_eq!(libc::pthread_barrierattr_destroy(&mut attr), 0);
-            }
+        let mut barrier = Self { inner, slot: None };
+
+        if register {
+            barrier.slot = barrier.register();
         }
 
-        Ok(Self(inner))
+        Ok(barrier)
     }
 
+    /// Reopen the existing barrier `name` and initialize its `pthread` object from
+    /// scratch, discarding any participant registrations. Intended for crash recovery
+    /// tooling, when a process died while using the barrier and left it in a bad state.
+    /// Fails with `ENOENT` if the barrier doesn't exist.
+    ///
+    /// Only safe when no process is using the barrier: any waiting threads are
+    /// left blocked on the old state, and the old state is never destroyed.
+    pub fn force_reinit(name: String, thread_count: u32) -> crate::Result<Self> {
+        let inner = Shm::<Inner>::with_options(
+            name,
+            crate::raw::Options {
+                attach: true,
+                ..crate::raw::Options::default()
+            },
+        )?;
+        Self::init(&inner, thread_count)?;
+
+        let barrier = Self { inner, slot: None };
+        for pid in barrier.pids() {
+            pid.store(0, Ordering::Release);
+        }
+        barrier.arrived_count().store(0, Ordering::Release);
+
+        Ok(barrier)
+    }
+
+    #[deprecated(note = "use `Barrier::wait_role`, which returns `Role::Serial` instead of `true`")]
     pub fn wait(&self) -> crate::Result<bool> {
-        match unsafe { libc::pthread_barrier_wait(self.0.address().as_ptr()) } {
-            libc::PTHREAD_BARRIER_SERIAL_THREAD => Ok(true),
-            0 => Ok(false),
+        self.wait_role().map(|role| role == Role::Serial)
+    }
+
+    pub fn wait_role(&self) -> crate::Result<Role> {
+        self.arrive();
+        self.pthread_wait()
+    }
+
+    /// Like [`Barrier::wait_role`], but give up after `timeout`, measured against
+    /// the configured [`Clock`]. On timeout, returns `Error::ParticipantDied` if a
+    /// registered participant has exited, and `None` otherwise.
+    ///
+    /// Note: `pthread_barrier_wait` has no timed variant, so this polls until
+    /// every participant has arrived before entering it.
+    pub fn wait_timeout(&self, timeout: Duration) -> crate::Result<Option<Role>> {
+        let clock = Clock::current();
+        let deadline = clock.deadline(timeout)?;
+        let phase = self.arrive();
+
+        while self.phase().load(Ordering::Acquire) >> 32 == phase {
+            if clock.is_past(&deadline)? {
+                // The last participant may have arrived in the meantime,
+                // in which case the phase is complete and we can't withdraw.
+                if self.withdraw(phase) {
+                    self.check_participants()?;
+                    return Ok(None);
+                }
+                break;
+            }
+
+            std::thread::sleep(Self::POLL_INTERVAL);
+        }
+
+        self.pthread_wait().map(Some)
+    }
+
+    // Count this thread as arrived, returning the phase it arrived in.
+    fn arrive(&self) -> u64 {
+        let thread_count = u64::from(unsafe { self.inner.get().thread_count });
+        let previous = self
+            .phase()
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |packed| {
+                let (phase, arrived) = (packed >> 32, packed & u64::from(u32::MAX));
+                Some(match arrived + 1 >= thread_count {
+                    true => (phase + 1) << 32,
+                    false => packed + 1,
+                })
+            })
+            .unwrap();
+        previous >> 32
+    }
+
+    // Undo `arrive`, unless `phase` has already completed.
+    fn withdraw(&self, phase: u64) -> bool {
+        self.phase()
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |packed| {
+                (packed >> 32 == phase).then(|| packed - 1)
+            })
+            .is_ok()
+    }
+
+    fn pthread_wait(&self) -> crate::Result<Role> {
+        self.arrived_count().fetch_add(1, Ordering::AcqRel);
+        let result = unsafe { libc::pthread_barrier_wait(self.barrier()) };
+        self.arrived_count().fetch_sub(1, Ordering::AcqRel);
+
+        match result {
+            libc::PTHREAD_BARRIER_SERIAL_THREAD => Ok(Role::Serial),
+            0 => Ok(Role::Member),
             error => Err(crate::Error::Libc {
                 name: "pthread_barrier_wait",
                 source: std::io::Error::from_raw_os_error(error),
@@ -61,8 +223,140 @@ impl Barrier {
         }
     }
 
+    /// Number of threads currently blocked in [`Barrier::wait_role`].
+    ///
+    /// Note: this is a snapshot, and briefly includes threads released from
+    /// the previous phase that have not yet returned.
+    pub fn arrived(&self) -> u32 {
+        self.arrived_count().load(Ordering::Acquire)
+    }
+
+    /// Destroy and reinitialize the barrier with its original thread count,
+    /// e.g. to reuse it for another round of a multi-process computation
+    /// without unlinking the segment.
+    ///
+    /// Only safe when no thread is blocked in [`Barrier::wait_role`]. Fails with
+    /// `Error::NotCreator` if this process did not create the barrier.
+    pub fn reset(&self) -> crate::Result<()> {
+        if self.inner.role() != SegmentRole::Creator {
+            return Err(crate::Error::NotCreator);
+        }
+
+        unsafe { try_pthread!(libc::pthread_barrier_destroy(self.barrier()))? }
+        let thread_count = unsafe { self.inner.get().thread_count };
+        Self::init(&self.inner, thread_count)?;
+        self.arrived_count().store(0, Ordering::Release);
+        Ok(())
+    }
+
+    /// Check whether any registered participant has exited, returning
+    /// `Error::ParticipantDied` for the first one found.
+    ///
+    /// Note: `pthread_barrier_wait` has no timed variant, so this is meant to
+    /// be polled by a watchdog while another thread is blocked in [`Barrier::wait_role`].
+    /// See [`Barrier::wait_timeout`] to check automatically instead.
+    pub fn check_participants(&self) -> crate::Result<()> {
+        for pid in self.pids() {
+            let pid = pid.load(Ordering::Acquire);
+
+            if pid == 0 {
+                continue;
+            }
+
+            // `EPERM` means the process exists but belongs to another user.
+            if unsafe { libc::kill(pid, 0) } == -1
+                && io::Error::last_os_error().raw_os_error() == Some(libc::ESRCH)
+            {
+                return Err(crate::Error::ParticipantDied { pid });
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn unlink(&mut self) -> crate::Result<()> {
-        unsafe { try_pthread!(libc::pthread_barrier_destroy(self.0.address().as_ptr()))? }
-        self.0.unlink()
+        unsafe { try_pthread!(libc::pthread_barrier_destroy(self.barrier()))? }
+        self.inner.unlink()
+    }
+
+    fn init(inner: &Shm<Inner>, thread_count: u32) -> crate::Result<()> {
+        let mut attr = unsafe {
+            let mut attr = MaybeUninit::<libc::pthread_barrierattr_t>::zeroed();
+            try_pthread!(libc::pthread_barrierattr_init(attr.as_mut_ptr()))?;
+            try_pthread!(libc::pthread_barrierattr_setpshared(
+                attr.as_mut_ptr(),
+                libc::PTHREAD_PROCESS_SHARED
+            ))?;
+            attr.assume_init()
+        };
+
+        unsafe {
+            let inner = inner.address().as_ptr();
+            try_pthread!(libc::pthread_barrier_init(
+                &raw mut (*inner).barrier,
+                &attr,
+                thread_count
+            ))?;
+            (&raw mut (*inner).thread_count).write(thread_count);
+            (&raw mut (*inner).phase).write(AtomicU64::new(0));
+        }
+
+        unsafe {
+            assert_eq!(libc::pthread_barrierattr_destroy(&mut attr), 0);
+        }
+
+        Ok(())
+    }
+
+    fn register(&self) -> Option<usize> {
+        let pid = unsafe { libc::getpid() };
+        let slot = self.pids().iter().position(|slot| {
+            slot.compare_exchange(0, pid, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+        });
+
+        if slot.is_none() {
+            log::warn!(
+                "Failed to register PID {} with barrier: all {} slots taken",
+                pid,
+                Self::MAX_PARTICIPANTS,
+            );
+        }
+
+        slot
+    }
+
+    fn barrier(&self) -> *mut libc::pthread_barrier_t {
+        unsafe { &raw mut (*self.inner.address().as_ptr()).barrier }
+    }
+
+    fn pids(&self) -> &[AtomicI32; Self::MAX_PARTICIPANTS] {
+        unsafe { &self.inner.address().as_ref().pids }
+    }
+
+    fn arrived_count(&self) -> &AtomicU32 {
+        unsafe { &self.inner.address().as_ref().arrived }
+    }
+
+    fn phase(&self) -> &AtomicU64 {
+        unsafe { &self.inner.address().as_ref().phase }
+    }
+}
+
+impl crate::Teardown for Barrier {
+    fn destroy(&mut self) -> crate::Result<()> {
+        unsafe { try_pthread!(libc::pthread_barrier_destroy(self.barrier())) }
+    }
+
+    fn unlink(&mut self) -> crate::Result<()> {
+        self.inner.unlink()
+    }
+}
+
+impl Drop for Barrier {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot {
+            self.pids()[slot].store(0, Ordering::Release);
+        }
     }
 }
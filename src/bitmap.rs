@@ -0,0 +1,189 @@
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
+
+use crate::Raw;
+
+/// Fixed-size bitmap with atomic operations across processes,
+/// e.g. for tracking occupied slots in a shared slab.
+pub struct Bitmap {
+    inner: Raw,
+    len: usize,
+}
+
+unsafe impl Sync for Bitmap {}
+unsafe impl Send for Bitmap {}
+
+#[cfg(feature = "builder")]
+#[bon::bon]
+impl Bitmap {
+    /// All processes must agree on `len`, the number of bits.
+    #[builder]
+    pub fn new(name: String, #[builder(default)] create: bool, len: usize) -> crate::Result<Self> {
+        Self::named(name, len, create)
+    }
+}
+
+impl Bitmap {
+    /// Shorthand for `Bitmap::builder().name(name).len(len).create(create).build()`.
+    pub fn named(name: String, len: usize, create: bool) -> crate::Result<Self> {
+        // Segments can't be empty, so an empty bitmap still maps one word.
+        let words = len.div_ceil(u64::BITS as usize).max(1);
+        let inner = Raw::named(name, words * size_of::<AtomicU64>(), create)?;
+        Ok(Self { inner, len })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Set bit `index`, returning its previous value.
+    pub fn set(&self, index: usize) -> bool {
+        let (word, bit) = self.locate(index);
+        word.fetch_or(bit, Ordering::AcqRel) & bit != 0
+    }
+
+    /// Clear bit `index`, returning its previous value.
+    pub fn clear(&self, index: usize) -> bool {
+        let (word, bit) = self.locate(index);
+        word.fetch_and(!bit, Ordering::AcqRel) & bit != 0
+    }
+
+    pub fn test(&self, index: usize) -> bool {
+        let (word, bit) = self.locate(index);
+        word.load(Ordering::Acquire) & bit != 0
+    }
+
+    /// Find and atomically set the first clear bit, so that concurrent
+    /// callers never claim the same index. Returns `None` if all bits are set.
+    pub fn find_first_zero(&self) -> Option<usize> {
+        for (index, word) in self.words().iter().enumerate() {
+            let mask = self.mask(index);
+            let mut current = word.load(Ordering::Relaxed);
+
+            loop {
+                let free = !current & mask;
+                if free == 0 {
+                    break;
+                }
+
+                let bit = free.trailing_zeros();
+                match word.compare_exchange_weak(
+                    current,
+                    current | 1 << bit,
+                    Ordering::AcqRel,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => return Some(index * u64::BITS as usize + bit as usize),
+                    Err(actual) => current = actual,
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn unlink(&mut self) -> crate::Result<()> {
+        self.inner.unlink()
+    }
+
+    fn locate(&self, index: usize) -> (&AtomicU64, u64) {
+        assert!(
+            index < self.len,
+            "Bitmap index {index} out of bounds for length {}",
+            self.len,
+        );
+
+        let bits = u64::BITS as usize;
+        (&self.words()[index / bits], 1 << (index % bits))
+    }
+
+    // Mask out trailing bits of the last word, which are past the end of the bitmap.
+    fn mask(&self, index: usize) -> u64 {
+        let bits = u64::BITS as usize;
+        match self.len - index * bits {
+            remaining if remaining < bits => (1 << remaining) - 1,
+            _ => u64::MAX,
+        }
+    }
+
+    fn words(&self) -> &[AtomicU64] {
+        unsafe {
+            core::slice::from_raw_parts(
+                self.inner.address().cast::<AtomicU64>().as_ptr(),
+                self.len.div_ceil(u64::BITS as usize),
+            )
+        }
+    }
+}
+
+impl crate::Teardown for Bitmap {
+    fn unlink(&mut self) -> crate::Result<()> {
+        Bitmap::unlink(self)
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use core::sync::atomic::Ordering;
+
+    use super::Bitmap;
+    use crate::testing;
+
+    const LEN: usize = 1000;
+    const PROCESSES: usize = 8;
+
+    fn name(suffix: &str) -> String {
+        testing::name("bitmap", suffix)
+    }
+
+    #[test]
+    fn empty() {
+        let mut bitmap = Bitmap::builder()
+            .name(name("empty"))
+            .create(true)
+            .len(0)
+            .build()
+            .unwrap();
+
+        assert!(bitmap.is_empty());
+        assert_eq!(bitmap.find_first_zero(), None);
+        bitmap.unlink().unwrap();
+    }
+
+    #[test]
+    fn find_first_zero_claims_each_bit_once() {
+        let mut bitmap = Bitmap::builder()
+            .name(name("bits"))
+            .create(true)
+            .len(LEN)
+            .build()
+            .unwrap();
+
+        // Number of times each bit was claimed, across all processes.
+        let counters = testing::Counters::new(name("claims"), LEN);
+        let claims = counters.as_slice();
+
+        let children = (0..PROCESSES)
+            .map(|_| {
+                testing::fork_child(|| {
+                    while let Some(index) = bitmap.find_first_zero() {
+                        claims[index].fetch_add(1, Ordering::Relaxed);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        testing::wait(children);
+
+        for (index, claim) in claims.iter().enumerate() {
+            assert_eq!(claim.load(Ordering::Relaxed), 1, "bit {index} claimed");
+            assert!(bitmap.test(index));
+        }
+
+        bitmap.unlink().unwrap();
+    }
+}
@@ -0,0 +1,104 @@
+//! Opt-in registry of segments created by this process, so they can be
+//! unlinked together instead of leaking in `/dev/shm` when the process panics
+//! or otherwise exits without unlinking them.
+//!
+//! Only segments this process created are tracked, so peers' segments that
+//! it merely attached to are left in place.
+
+use std::sync::Mutex;
+
+use crate::Backend;
+
+// `None` until `register` is called, so processes that don't opt in
+// don't accumulate entries.
+static REGISTRY: Mutex<Option<Vec<Entry>>> = Mutex::new(None);
+
+struct Entry {
+    name: String,
+    backend: Backend,
+}
+
+/// Unlinks every tracked segment when dropped, e.g. when held in `main`
+/// while a panic unwinds. See [`register`].
+#[must_use = "segments are unlinked when the guard is dropped"]
+pub struct Guard(());
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if let Err(error) = unlink_all() {
+            log::error!("Failed to unlink registered segments: {}", error);
+        }
+    }
+}
+
+/// Start tracking segments created by this process. Segments created
+/// before the first call are not tracked.
+pub fn register() -> Guard {
+    REGISTRY
+        .lock()
+        .unwrap_or_else(|poison| poison.into_inner())
+        .get_or_insert_with(Vec::new);
+    Guard(())
+}
+
+/// Unlink every tracked segment that still exists, e.g. to clean up
+/// deterministically at the end of an integration test.
+///
+/// Attempts every segment, returning the first error encountered.
+pub fn unlink_all() -> crate::Result<()> {
+    let entries = match REGISTRY
+        .lock()
+        .unwrap_or_else(|poison| poison.into_inner())
+        .as_mut()
+    {
+        Some(entries) => core::mem::take(entries),
+        None => return Ok(()),
+    };
+
+    let mut result = Ok(());
+    for entry in entries {
+        match entry.backend.unlink(&entry.name) {
+            Ok(()) => log::info!("Unlinked registered segment: {}", entry.name),
+            Err(error) if error.is_not_found() => (),
+            Err(error) => {
+                if result.is_ok() {
+                    result = Err(error);
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Record that this process created segment `name`, if registered.
+pub(crate) fn track(backend: &Backend, name: &str) {
+    // Anonymous backends have nothing to unlink.
+    let backend = match backend {
+        Backend::Shm(_) => Backend::Shm(crate::backend::Shm::default()),
+        Backend::Path(path) => Backend::Path(path.clone()),
+        _ => return,
+    };
+
+    if let Some(entries) = REGISTRY
+        .lock()
+        .unwrap_or_else(|poison| poison.into_inner())
+        .as_mut()
+    {
+        entries.push(Entry {
+            name: name.to_owned(),
+            backend,
+        });
+    }
+}
+
+/// Stop tracking segment `name`, e.g. after it is unlinked explicitly.
+pub(crate) fn untrack(name: &str) {
+    if let Some(entries) = REGISTRY
+        .lock()
+        .unwrap_or_else(|poison| poison.into_inner())
+        .as_mut()
+    {
+        entries.retain(|entry| entry.name != name);
+    }
+}
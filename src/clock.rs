@@ -0,0 +1,75 @@
+use core::mem::MaybeUninit;
+use core::sync::atomic::AtomicU8;
+use core::sync::atomic::Ordering;
+use core::time::Duration;
+
+use crate::try_libc;
+
+static CLOCK: AtomicU8 = AtomicU8::new(Clock::Monotonic as u8);
+
+/// Clock used by all timed primitives to compute absolute deadlines, so that
+/// primitives mixed in one protocol agree on when a timeout expires.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[repr(u8)]
+pub enum Clock {
+    /// `CLOCK_MONOTONIC`, which is unaffected by changes to the system time.
+    #[default]
+    Monotonic,
+    /// `CLOCK_REALTIME`, which jumps when the system time is changed.
+    Realtime,
+}
+
+/// Configure the clock used by timed primitives. Defaults to [`Clock::Monotonic`].
+///
+/// Must be called before creating any timed primitive, since process-shared
+/// objects such as condition variables record their clock when initialized.
+pub fn set_clock(clock: Clock) {
+    CLOCK.store(clock as u8, Ordering::Relaxed);
+}
+
+impl Clock {
+    /// Clock configured by [`set_clock`].
+    pub fn current() -> Self {
+        match CLOCK.load(Ordering::Relaxed) {
+            0 => Clock::Monotonic,
+            _ => Clock::Realtime,
+        }
+    }
+
+    pub fn id(&self) -> libc::clockid_t {
+        match self {
+            Clock::Monotonic => libc::CLOCK_MONOTONIC,
+            Clock::Realtime => libc::CLOCK_REALTIME,
+        }
+    }
+
+    /// Absolute deadline `timeout` from now on this clock, for `pthread` and
+    /// `sem` functions taking an absolute `timespec`.
+    pub fn deadline(&self, timeout: Duration) -> crate::Result<libc::timespec> {
+        let now = self.now()?;
+        let nanos = now.tv_nsec as u64 + u64::from(timeout.subsec_nanos());
+        Ok(libc::timespec {
+            tv_sec: now
+                .tv_sec
+                .saturating_add(timeout.as_secs() as libc::time_t)
+                .saturating_add((nanos / 1_000_000_000) as libc::time_t),
+            tv_nsec: (nanos % 1_000_000_000) as libc::c_long,
+        })
+    }
+
+    /// Whether `deadline`, from [`Clock::deadline`], has passed.
+    pub(crate) fn is_past(&self, deadline: &libc::timespec) -> crate::Result<bool> {
+        let now = self.now()?;
+        Ok((now.tv_sec, now.tv_nsec) >= (deadline.tv_sec, deadline.tv_nsec))
+    }
+
+    fn now(&self) -> crate::Result<libc::timespec> {
+        let mut now = MaybeUninit::<libc::timespec>::uninit();
+        unsafe {
+            try_libc!(libc::clock_gettime(self.id(), now.as_mut_ptr()))?;
+            Ok(now.assume_init())
+        }
+    }
+}
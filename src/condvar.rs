@@ -0,0 +1,139 @@
+#[cfg(feature = "builder")]
+use core::mem::MaybeUninit;
+use core::time::Duration;
+
+use crate::Clock;
+use crate::MutexGuard;
+#[cfg(feature = "builder")]
+use crate::Numa;
+#[cfg(feature = "builder")]
+use crate::Populate;
+use crate::Shm;
+use crate::try_pthread;
+
+/// Process-shared `pthread` condition variable, used together with a [`crate::Mutex`].
+pub struct CondVar {
+    inner: Shm<Inner>,
+}
+
+#[repr(C)]
+struct Inner {
+    cond: libc::pthread_cond_t,
+    // Clock configured by the creator, which timed waits must compute deadlines against.
+    clock: libc::clockid_t,
+}
+
+unsafe impl Sync for CondVar {}
+unsafe impl Send for CondVar {}
+
+#[cfg(feature = "builder")]
+#[bon::bon]
+impl CondVar {
+    /// Timed waits use the [`Clock`] configured in the creating process.
+    #[builder]
+    pub fn new(
+        name: String,
+        #[builder(default)] create: bool,
+        numa: Option<Numa>,
+        populate: Option<Populate>,
+    ) -> crate::Result<Self> {
+        let inner = Shm::<Inner>::builder()
+            .name(name)
+            .create(create)
+            .maybe_numa(numa)
+            .maybe_populate(populate)
+            .build()?;
+
+        if create {
+            let clock = Clock::current().id();
+            let mut attr = unsafe {
+                let mut attr = MaybeUninit::<libc::pthread_condattr_t>::zeroed();
+                try_pthread!(libc::pthread_condattr_init(attr.as_mut_ptr()))?;
+                try_pthread!(libc::pthread_condattr_setpshared(
+                    attr.as_mut_ptr(),
+                    libc::PTHREAD_PROCESS_SHARED
+                ))?;
+                try_pthread!(libc::pthread_condattr_setclock(attr.as_mut_ptr(), clock))?;
+                attr.assume_init()
+            };
+
+            unsafe {
+                let inner = inner.address().as_ptr();
+                try_pthread!(libc::pthread_cond_init(&raw mut (*inner).cond, &attr))?;
+                (&raw mut (*inner).clock).write(clock);
+            }
+
+            unsafe {
+                assert_eq!(libc::pthread_condattr_destroy(&mut attr), 0);
+            }
+        }
+
+        Ok(Self { inner })
+    }
+}
+
+impl CondVar {
+    /// Atomically release the mutex held by `guard` and block until notified,
+    /// then reacquire the mutex. Spurious wakeups are possible.
+    pub fn wait<'mutex>(&self, guard: MutexGuard<'mutex>) -> crate::Result<MutexGuard<'mutex>> {
+        unsafe { try_pthread!(libc::pthread_cond_wait(self.cond(), guard.mutex())) }?;
+        Ok(guard)
+    }
+
+    /// Like [`CondVar::wait`], but give up after `timeout`. Returns `true` if the
+    /// wait timed out.
+    pub fn wait_timeout<'mutex>(
+        &self,
+        guard: MutexGuard<'mutex>,
+        timeout: Duration,
+    ) -> crate::Result<(MutexGuard<'mutex>, bool)> {
+        let clock = match unsafe { (&raw const (*self.inner.address().as_ptr()).clock).read() } {
+            libc::CLOCK_REALTIME => Clock::Realtime,
+            _ => Clock::Monotonic,
+        };
+
+        let deadline = clock.deadline(timeout)?;
+        match unsafe {
+            try_pthread!(libc::pthread_cond_timedwait(
+                self.cond(),
+                guard.mutex(),
+                &deadline
+            ))
+        } {
+            Ok(()) => Ok((guard, false)),
+            Err(crate::Error::Libc { source, .. })
+                if source.raw_os_error() == Some(libc::ETIMEDOUT) =>
+            {
+                Ok((guard, true))
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn notify_one(&self) -> crate::Result<()> {
+        unsafe { try_pthread!(libc::pthread_cond_signal(self.cond())) }
+    }
+
+    pub fn notify_all(&self) -> crate::Result<()> {
+        unsafe { try_pthread!(libc::pthread_cond_broadcast(self.cond())) }
+    }
+
+    pub fn unlink(&mut self) -> crate::Result<()> {
+        unsafe { try_pthread!(libc::pthread_cond_destroy(self.cond()))? }
+        self.inner.unlink()
+    }
+
+    fn cond(&self) -> *mut libc::pthread_cond_t {
+        unsafe { &raw mut (*self.inner.address().as_ptr()).cond }
+    }
+}
+
+impl crate::Teardown for CondVar {
+    fn destroy(&mut self) -> crate::Result<()> {
+        unsafe { try_pthread!(libc::pthread_cond_destroy(self.cond())) }
+    }
+
+    fn unlink(&mut self) -> crate::Result<()> {
+        self.inner.unlink()
+    }
+}
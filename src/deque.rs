@@ -0,0 +1,271 @@
+use core::hint;
+use core::marker::PhantomData;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering;
+
+use crate::Raw;
+
+/// Fixed-capacity double-ended queue shared across processes, e.g. for a
+/// work-stealing scheduler where owners push and pop at one end while
+/// thieves steal from the other.
+///
+/// Operations serialize on a spin lock in the control block, so any number
+/// of processes may push or pop at either end.
+///
+/// Note: a process that dies while holding the lock permanently blocks the queue.
+pub struct Deque<T> {
+    inner: Raw,
+    capacity: usize,
+    r#type: PhantomData<T>,
+}
+
+#[repr(C)]
+struct Control {
+    lock: AtomicBool,
+    // Slot index of the front element, in `0..capacity`.
+    head: AtomicUsize,
+    len: AtomicUsize,
+}
+
+unsafe impl<T: Copy + Send> Sync for Deque<T> {}
+unsafe impl<T: Copy + Send> Send for Deque<T> {}
+
+#[cfg(feature = "builder")]
+#[bon::bon]
+impl<T: Copy> Deque<T> {
+    /// All processes must agree on `T` and `capacity`, the maximum number of elements.
+    /// Fails with `Error::ZeroCapacity` if `capacity` is zero.
+    #[builder]
+    pub fn new(
+        name: String,
+        #[builder(default)] create: bool,
+        capacity: usize,
+    ) -> crate::Result<Self> {
+        Self::named(name, capacity, create)
+    }
+}
+
+impl<T: Copy> Deque<T> {
+    /// Shorthand for `Deque::builder().name(name).capacity(capacity).create(create).build()`.
+    pub fn named(name: String, capacity: usize, create: bool) -> crate::Result<Self> {
+        if capacity == 0 {
+            return Err(crate::Error::ZeroCapacity);
+        }
+
+        let inner = Raw::named(name, Self::OFFSET + capacity * size_of::<T>(), create)?;
+        Ok(Self {
+            inner,
+            capacity,
+            r#type: PhantomData,
+        })
+    }
+
+    const OFFSET: usize = size_of::<Control>().next_multiple_of(align_of::<T>());
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.control().len.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Push `value` onto the front, returning it back if the queue is full.
+    pub fn push_front(&self, value: T) -> Result<(), T> {
+        self.locked(|head, len| {
+            if *len == self.capacity {
+                return Err(value);
+            }
+
+            *head = (*head + self.capacity - 1) % self.capacity;
+            *len += 1;
+            unsafe { self.slot(*head).write(value) };
+            Ok(())
+        })
+    }
+
+    /// Push `value` onto the back, returning it back if the queue is full.
+    pub fn push_back(&self, value: T) -> Result<(), T> {
+        self.locked(|head, len| {
+            if *len == self.capacity {
+                return Err(value);
+            }
+
+            unsafe { self.slot((*head + *len) % self.capacity).write(value) };
+            *len += 1;
+            Ok(())
+        })
+    }
+
+    pub fn pop_front(&self) -> Option<T> {
+        self.locked(|head, len| {
+            if *len == 0 {
+                return None;
+            }
+
+            let value = unsafe { self.slot(*head).read() };
+            *head = (*head + 1) % self.capacity;
+            *len -= 1;
+            Some(value)
+        })
+    }
+
+    pub fn pop_back(&self) -> Option<T> {
+        self.locked(|head, len| {
+            if *len == 0 {
+                return None;
+            }
+
+            *len -= 1;
+            Some(unsafe { self.slot((*head + *len) % self.capacity).read() })
+        })
+    }
+
+    pub fn unlink(&mut self) -> crate::Result<()> {
+        self.inner.unlink()
+    }
+
+    // Run `apply` on the current head and length under the lock.
+    fn locked<R>(&self, apply: impl FnOnce(&mut usize, &mut usize) -> R) -> R {
+        let control = self.control();
+        while control
+            .lock
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            hint::spin_loop();
+        }
+
+        let mut head = control.head.load(Ordering::Relaxed);
+        let mut len = control.len.load(Ordering::Relaxed);
+        let result = apply(&mut head, &mut len);
+        control.head.store(head, Ordering::Relaxed);
+        control.len.store(len, Ordering::Relaxed);
+
+        control.lock.store(false, Ordering::Release);
+        result
+    }
+
+    fn control(&self) -> &Control {
+        unsafe { self.inner.address().cast::<Control>().as_ref() }
+    }
+
+    fn slot(&self, index: usize) -> *mut T {
+        unsafe {
+            self.inner
+                .address()
+                .cast::<u8>()
+                .add(Self::OFFSET)
+                .cast::<T>()
+                .add(index)
+                .as_ptr()
+        }
+    }
+}
+
+impl<T: Copy + 'static> crate::Teardown for Deque<T> {
+    fn unlink(&mut self) -> crate::Result<()> {
+        Deque::unlink(self)
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use core::sync::atomic::Ordering;
+
+    use super::Deque;
+    use crate::testing;
+
+    const CAPACITY: usize = 64;
+    const PRODUCERS: usize = 2;
+    const THIEVES: usize = 4;
+    const PER_PRODUCER: usize = 10_000;
+    const TOTAL: usize = PRODUCERS * PER_PRODUCER;
+
+    fn name(suffix: &str) -> String {
+        testing::name("deque", suffix)
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let error = Deque::<u64>::builder()
+            .name(name("zero"))
+            .create(true)
+            .capacity(0)
+            .build()
+            .err()
+            .unwrap();
+
+        assert!(matches!(error, crate::Error::ZeroCapacity));
+    }
+
+    #[test]
+    fn steal_from_both_ends() {
+        let mut deque = Deque::<u64>::builder()
+            .name(name("queue"))
+            .create(true)
+            .capacity(CAPACITY)
+            .build()
+            .unwrap();
+
+        // Number of times each value was popped, followed by the number of
+        // producers that have finished.
+        let counters = testing::Counters::new(name("pops"), TOTAL + 1);
+        let (pops, finished) = counters.as_slice().split_at(TOTAL);
+        let finished = &finished[0];
+
+        let mut children = Vec::new();
+
+        for producer in 0..PRODUCERS {
+            children.push(testing::fork_child(|| {
+                for index in 0..PER_PRODUCER {
+                    let mut value = (producer * PER_PRODUCER + index) as u64;
+                    loop {
+                        let pushed = match index % 2 {
+                            0 => deque.push_front(value),
+                            _ => deque.push_back(value),
+                        };
+                        match pushed {
+                            Ok(()) => break,
+                            Err(full) => value = full,
+                        }
+                    }
+                }
+                finished.fetch_add(1, Ordering::Release);
+            }));
+        }
+
+        for thief in 0..THIEVES {
+            children.push(testing::fork_child(|| {
+                loop {
+                    let done = finished.load(Ordering::Acquire) == PRODUCERS as u32;
+                    let popped = match thief % 2 {
+                        0 => deque.pop_front(),
+                        _ => deque.pop_back(),
+                    };
+                    match popped {
+                        Some(value) => {
+                            pops[value as usize].fetch_add(1, Ordering::Relaxed);
+                        }
+                        None if done => break,
+                        None => core::hint::spin_loop(),
+                    }
+                }
+            }));
+        }
+
+        testing::wait(children);
+
+        assert!(deque.is_empty());
+        for (value, pop) in pops.iter().enumerate() {
+            assert_eq!(pop.load(Ordering::Relaxed), 1, "value {value} popped");
+        }
+
+        deque.unlink().unwrap();
+    }
+}
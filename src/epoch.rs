@@ -0,0 +1,104 @@
+use core::ops::Deref;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
+
+use crate::Shm;
+
+/// Segment that can be unlinked and recreated under the same name, with a
+/// counter in a separate directory segment (`{name}.epoch`) so peers can
+/// detect that their mapping refers to a dead object and re-attach.
+///
+/// Note: POSIX shm gives no notification when a name is unlinked, so peers
+/// must poll [`Epoch::is_stale`].
+pub struct Epoch<T> {
+    inner: Shm<T>,
+    directory: Shm<AtomicU64>,
+    name: String,
+    epoch: u64,
+}
+
+impl<T> Epoch<T> {
+    /// Attach to the current incarnation of the segment, failing with `ENOENT`
+    /// if it or its directory doesn't exist, i.e. [`Epoch::recreate`] never ran.
+    pub fn attach(name: String) -> crate::Result<Self> {
+        let directory = Shm::open(Self::directory_name(&name))?;
+
+        loop {
+            let epoch = Self::load(&directory);
+            let inner = Shm::open(name.clone())?;
+
+            // Retry if the segment was recreated while we were opening it.
+            if epoch == Self::load(&directory) {
+                return Ok(Self {
+                    inner,
+                    directory,
+                    name,
+                    epoch,
+                });
+            }
+        }
+    }
+
+    /// Unlink the current segment, if any, and create a fresh one,
+    /// marking existing mappings as stale.
+    pub fn recreate(name: String) -> crate::Result<Self> {
+        // Created by the first call, and shared by later ones.
+        let directory =
+            Shm::with_options(Self::directory_name(&name), crate::raw::Options::default())?;
+        let inner = Shm::create(name.clone())?;
+        let epoch = unsafe { directory.address().as_ref() }.fetch_add(1, Ordering::AcqRel) + 1;
+
+        Ok(Self {
+            inner,
+            directory,
+            name,
+            epoch,
+        })
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Whether the segment has been recreated since this mapping was attached.
+    pub fn is_stale(&self) -> bool {
+        Self::load(&self.directory) != self.epoch
+    }
+
+    /// Re-attach to the current incarnation of the segment.
+    pub fn reattach(&mut self) -> crate::Result<()> {
+        *self = Self::attach(self.name.clone())?;
+        Ok(())
+    }
+
+    /// Unlink both the segment and its directory, e.g. once it will not be
+    /// recreated again. Existing mappings stay valid, but are not marked stale.
+    pub fn unlink(&mut self) -> crate::Result<()> {
+        // Unlink the directory even if the segment was already unlinked.
+        let inner = self.inner.unlink();
+        let directory = self.directory.unlink();
+        inner.and(directory)
+    }
+
+    fn directory_name(name: &str) -> String {
+        format!("{name}.epoch")
+    }
+
+    fn load(directory: &Shm<AtomicU64>) -> u64 {
+        unsafe { directory.address().as_ref() }.load(Ordering::Acquire)
+    }
+}
+
+impl<T> Deref for Epoch<T> {
+    type Target = Shm<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T: 'static> crate::Teardown for Epoch<T> {
+    fn unlink(&mut self) -> crate::Result<()> {
+        Epoch::unlink(self)
+    }
+}
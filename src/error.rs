@@ -15,33 +15,112 @@ pub enum Error {
         name: &'static str,
         source: io::Error,
     },
+    ArchMismatch {
+        big_endian: bool,
+        pointer_width: u8,
+    },
+    ParticipantDied {
+        pid: libc::pid_t,
+    },
+    SizeMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    SchemaMismatch {
+        expected: u64,
+        actual: u64,
+    },
+    Alignment {
+        required: usize,
+        actual: usize,
+    },
+    ReservationOverflow {
+        offset: usize,
+        size: usize,
+        capacity: usize,
+    },
+    MissingHeader,
+    InitTimeout,
+    HugePageUnavailable {
+        size: usize,
+        source: io::Error,
+    },
+    HugePageUnsupported {
+        size: usize,
+    },
+    MemlockLimit {
+        size: usize,
+        source: io::Error,
+    },
+    NumaNode {
+        node: usize,
+    },
+    NumaStripe {
+        stripe: usize,
+    },
+    UnknownBackend(String),
+    MissingLength,
+    LengthOverflow {
+        len: usize,
+    },
+    ZeroCapacity,
+    NotCreator,
 }
 
 impl Error {
+    /// Attach the shm `path` that a libc call failed on. Other errors already
+    /// carry their own context and are returned unchanged.
     pub(crate) fn with_path(self, path: backend::shm::Path) -> Self {
         match self {
-            Error::ShmName | Error::Shm { .. } => unreachable!(),
             Error::Libc { name, source } => Self::Shm { path, name, source },
+            error => error,
         }
     }
 
-    pub(crate) fn is_not_found(&self) -> bool {
+    /// Category of the error, e.g. to distinguish a segment that already exists
+    /// from one this process may not open.
+    pub fn kind(&self) -> ErrorKind {
         match self {
-            Error::Shm { source, .. } | Error::Libc { source, .. } => {
-                matches!(source.kind(), io::ErrorKind::NotFound)
-            }
-            _ => false,
+            Error::ShmName => ErrorKind::Name,
+            Error::SizeMismatch { .. } => ErrorKind::SizeMismatch,
+            Error::ZeroCapacity => ErrorKind::InvalidInput,
+            Error::NotCreator => ErrorKind::PermissionDenied,
+            Error::Shm { source, .. } | Error::Libc { source, .. } => match source.kind() {
+                io::ErrorKind::NotFound => ErrorKind::NotFound,
+                io::ErrorKind::AlreadyExists => ErrorKind::AlreadyExists,
+                io::ErrorKind::PermissionDenied => ErrorKind::PermissionDenied,
+                _ => ErrorKind::Other,
+            },
+            _ => ErrorKind::Other,
         }
     }
 
-    pub(crate) fn is_already_exists(&self) -> bool {
-        match self {
-            Error::Libc { name: _, source } => {
-                matches!(source.kind(), io::ErrorKind::AlreadyExists)
-            }
-            _ => false,
-        }
+    pub fn is_not_found(&self) -> bool {
+        self.kind() == ErrorKind::NotFound
     }
+
+    pub(crate) fn is_permission_denied(&self) -> bool {
+        self.kind() == ErrorKind::PermissionDenied
+    }
+
+    pub fn is_already_exists(&self) -> bool {
+        self.kind() == ErrorKind::AlreadyExists
+    }
+}
+
+/// Category of an [`Error`], returned by [`Error::kind`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Invalid segment name, e.g. too long for the shm backend.
+    Name,
+    NotFound,
+    AlreadyExists,
+    PermissionDenied,
+    SizeMismatch,
+    /// Invalid argument, e.g. a zero capacity.
+    InvalidInput,
+    Other,
 }
 
 impl Display for Error {
@@ -52,12 +131,73 @@ impl Display for Error {
                 path,
                 name,
                 source: _,
+            } => write!(f, "{name} error ({path})"),
+            Self::Libc { name, source: _ } => write!(f, "{name} error"),
+            Self::ArchMismatch {
+                big_endian,
+                pointer_width,
             } => write!(
                 f,
-                "{name} error ({})",
-                std::str::from_utf8(path).unwrap_or("")
+                "segment created by incompatible architecture ({} endian, {pointer_width}-bit pointers)",
+                if *big_endian { "big" } else { "little" },
             ),
-            Self::Libc { name, source: _ } => write!(f, "{name} error"),
+            Self::ParticipantDied { pid } => write!(f, "barrier participant {pid} died"),
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "expected segment of at least {expected} bytes, found {actual} bytes"
+            ),
+            Self::SchemaMismatch { expected, actual } => write!(
+                f,
+                "expected segment with schema {expected:#x}, found {actual:#x}"
+            ),
+            Self::Alignment { required, actual } => write!(
+                f,
+                "expected alignment of {required:#x} bytes, found {actual:#x} bytes"
+            ),
+            Self::ReservationOverflow {
+                offset,
+                size,
+                capacity,
+            } => write!(
+                f,
+                "mapping of {size:#x} bytes at offset {offset:#x} exceeds reservation of {capacity:#x} bytes"
+            ),
+            Self::MissingHeader => write!(
+                f,
+                "segment was created without a header, or its creator never published it"
+            ),
+            Self::InitTimeout => write!(f, "segment contents were never initialized"),
+            Self::HugePageUnavailable { size, .. } => write!(
+                f,
+                "not enough free huge pages of {size:#x} bytes, see /proc/sys/vm/nr_hugepages"
+            ),
+            Self::HugePageUnsupported { size } => write!(
+                f,
+                "huge pages of {size:#x} bytes require an anonymous mapping or a file on hugetlbfs"
+            ),
+            Self::MemlockLimit { size, .. } => write!(
+                f,
+                "cannot lock {size:#x} bytes into memory, see RLIMIT_MEMLOCK or CAP_IPC_LOCK"
+            ),
+            Self::NumaNode { node } => write!(
+                f,
+                "NUMA node {node} exceeds the supported maximum of {}",
+                crate::Numa::MAX_NODES - 1,
+            ),
+            Self::NumaStripe { stripe } => write!(
+                f,
+                "NUMA interleave stripe of {stripe:#x} bytes is not a multiple of the page size",
+            ),
+            Self::UnknownBackend(backend) => write!(f, "unknown backend {backend:?}"),
+            Self::MissingLength => write!(f, "creating a slice segment requires a length"),
+            Self::LengthOverflow { len } => {
+                write!(
+                    f,
+                    "slice segment of {len} elements overflows the address space"
+                )
+            }
+            Self::ZeroCapacity => write!(f, "capacity must be nonzero"),
+            Self::NotCreator => write!(f, "only the process that created the segment can do this"),
         }
     }
 }
@@ -65,8 +205,27 @@ impl Display for Error {
 impl core::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::ShmName => None,
-            Self::Shm { source, .. } | Self::Libc { source, .. } => Some(source),
+            Self::ShmName
+            | Self::ArchMismatch { .. }
+            | Self::ParticipantDied { .. }
+            | Self::SizeMismatch { .. }
+            | Self::SchemaMismatch { .. }
+            | Self::Alignment { .. }
+            | Self::ReservationOverflow { .. }
+            | Self::MissingHeader
+            | Self::InitTimeout
+            | Self::HugePageUnsupported { .. }
+            | Self::NumaNode { .. }
+            | Self::NumaStripe { .. }
+            | Self::UnknownBackend(_)
+            | Self::MissingLength
+            | Self::LengthOverflow { .. }
+            | Self::ZeroCapacity
+            | Self::NotCreator => None,
+            Self::Shm { source, .. }
+            | Self::Libc { source, .. }
+            | Self::HugePageUnavailable { source, .. }
+            | Self::MemlockLimit { source, .. } => Some(source),
         }
     }
 }
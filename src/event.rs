@@ -0,0 +1,177 @@
+use core::ptr;
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::Ordering;
+use core::time::Duration;
+
+use crate::Clock;
+#[cfg(feature = "builder")]
+use crate::Numa;
+#[cfg(feature = "builder")]
+use crate::Populate;
+use crate::Shm;
+use crate::try_libc;
+
+/// Process-shared flag that processes can block on until it is set, e.g. to
+/// signal that a segment has been initialized. Lighter weight than a
+/// [`crate::CondVar`], since waiting and waking are single `futex` calls.
+pub struct Event {
+    inner: Shm<AtomicU32>,
+}
+
+unsafe impl Sync for Event {}
+unsafe impl Send for Event {}
+
+#[cfg(feature = "builder")]
+#[bon::bon]
+impl Event {
+    /// Newly created events are cleared.
+    #[builder]
+    pub fn new(
+        name: String,
+        #[builder(default)] create: bool,
+        numa: Option<Numa>,
+        populate: Option<Populate>,
+    ) -> crate::Result<Self> {
+        let inner = Shm::<AtomicU32>::builder()
+            .name(name)
+            .create(create)
+            .maybe_numa(numa)
+            .maybe_populate(populate)
+            .build()?;
+
+        Ok(Self { inner })
+    }
+}
+
+impl Event {
+    const CLEAR: u32 = 0;
+    const SET: u32 = 1;
+
+    // `FUTEX_BITSET_MATCH_ANY`, so bitset waits are woken by plain `FUTEX_WAKE`.
+    const MATCH_ANY: u32 = u32::MAX;
+
+    pub fn is_set(&self) -> bool {
+        self.state().load(Ordering::Acquire) == Self::SET
+    }
+
+    /// Set the flag, waking all waiting processes.
+    pub fn set(&self) -> crate::Result<()> {
+        self.state().store(Self::SET, Ordering::Release);
+        unsafe {
+            try_libc!(futex_syscall(
+                self.state().as_ptr(),
+                libc::FUTEX_WAKE,
+                i32::MAX as u32,
+                ptr::null(),
+                0,
+            ))
+        }?;
+        Ok(())
+    }
+
+    /// Clear the flag, so that subsequent waits block until it is set again.
+    pub fn clear(&self) {
+        self.state().store(Self::CLEAR, Ordering::Release);
+    }
+
+    /// Block until the flag is set.
+    pub fn wait(&self) -> crate::Result<()> {
+        while !self.is_set() {
+            self.futex_wait(None)?;
+        }
+        Ok(())
+    }
+
+    /// Like [`Event::wait`], but give up after `timeout`. Returns `false`
+    /// if the wait timed out.
+    ///
+    /// The deadline is computed against the configured [`Clock`].
+    pub fn wait_timeout(&self, timeout: Duration) -> crate::Result<bool> {
+        let clock = Clock::current();
+        let deadline = clock.deadline(timeout)?;
+        while !self.is_set() {
+            if !self.futex_wait(Some((clock, deadline)))? {
+                return Ok(self.is_set());
+            }
+        }
+        Ok(true)
+    }
+
+    pub fn unlink(&mut self) -> crate::Result<()> {
+        self.inner.unlink()
+    }
+
+    // Block while the flag is clear, returning early on wakeups or signals.
+    // Returns `false` once the absolute `deadline` on `clock` has passed.
+    fn futex_wait(&self, deadline: Option<(Clock, libc::timespec)>) -> crate::Result<bool> {
+        // Unlike `FUTEX_WAIT`, `FUTEX_WAIT_BITSET` takes an absolute timeout,
+        // measured against `CLOCK_MONOTONIC` unless `FUTEX_CLOCK_REALTIME` is set.
+        let (op, timeout) = match &deadline {
+            None => (libc::FUTEX_WAIT, ptr::null()),
+            Some((Clock::Monotonic, deadline)) => {
+                (libc::FUTEX_WAIT_BITSET, ptr::from_ref(deadline))
+            }
+            Some((Clock::Realtime, deadline)) => (
+                libc::FUTEX_WAIT_BITSET | libc::FUTEX_CLOCK_REALTIME,
+                ptr::from_ref(deadline),
+            ),
+        };
+
+        match unsafe {
+            try_libc!(futex_syscall(
+                self.state().as_ptr(),
+                op,
+                Self::CLEAR,
+                timeout,
+                Self::MATCH_ANY,
+            ))
+        } {
+            Ok(_) => Ok(true),
+            Err(crate::Error::Libc { source, .. })
+                if matches!(source.raw_os_error(), Some(libc::EAGAIN | libc::EINTR)) =>
+            {
+                Ok(true)
+            }
+            Err(crate::Error::Libc { source, .. })
+                if source.raw_os_error() == Some(libc::ETIMEDOUT) =>
+            {
+                Ok(false)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    fn state(&self) -> &AtomicU32 {
+        unsafe { self.inner.get() }
+    }
+}
+
+impl crate::Teardown for Event {
+    fn unlink(&mut self) -> crate::Result<()> {
+        self.inner.unlink()
+    }
+}
+
+// Call syscall since libc has no `futex` wrapper. Omits `FUTEX_PRIVATE_FLAG`
+// so that waiters in other processes are woken.
+//
+// https://man7.org/linux/man-pages/man2/futex.2.html
+unsafe fn futex_syscall(
+    address: *mut u32,
+    op: libc::c_int,
+    value: u32,
+    timeout: *const libc::timespec,
+    bitset: u32,
+) -> i64 {
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            address,
+            op,
+            value,
+            timeout,
+            ptr::null::<u32>(),
+            bitset,
+        )
+    }
+}
@@ -0,0 +1,101 @@
+use core::any::Any;
+
+use crate::Raw;
+use crate::Shm;
+use crate::ShmSlice;
+
+/// Segment or synchronization object that can be owned by a [`ShmGroup`].
+pub trait Teardown: Any {
+    /// Destroy any process-shared object (e.g. a `pthread` barrier) hosted in the
+    /// segment. Must be called before the segment is unmapped.
+    fn destroy(&mut self) -> crate::Result<()> {
+        Ok(())
+    }
+
+    /// Unlink the segment's name, without destroying or unmapping it.
+    fn unlink(&mut self) -> crate::Result<()>;
+}
+
+/// Owns several segments and synchronization objects, and tears them down in a
+/// safe order on drop: every member is destroyed before any is unmapped, so a
+/// live `pthread` object never outlives the mapping hosting it.
+///
+/// Members are unlinked (if enabled) after being destroyed but before being
+/// unmapped, which is equivalent since existing mappings keep the object alive.
+pub struct ShmGroup {
+    members: Vec<Box<dyn Teardown>>,
+    unlink: bool,
+}
+
+#[cfg(feature = "builder")]
+#[bon::bon]
+impl ShmGroup {
+    #[builder]
+    pub fn new(
+        /// Unlink all members on drop.
+        #[builder(default)]
+        unlink: bool,
+    ) -> Self {
+        Self {
+            members: Vec::new(),
+            unlink,
+        }
+    }
+}
+
+impl ShmGroup {
+    /// Add a member, returning its index for [`ShmGroup::get`].
+    /// Members are destroyed in reverse order of insertion.
+    pub fn push<M: Teardown>(&mut self, member: M) -> usize {
+        self.members.push(Box::new(member));
+        self.members.len() - 1
+    }
+
+    pub fn get<M: Teardown>(&self, index: usize) -> Option<&M> {
+        let member: &dyn Any = &**self.members.get(index)?;
+        member.downcast_ref()
+    }
+
+    pub fn get_mut<M: Teardown>(&mut self, index: usize) -> Option<&mut M> {
+        let member: &mut dyn Any = &mut **self.members.get_mut(index)?;
+        member.downcast_mut()
+    }
+}
+
+impl Drop for ShmGroup {
+    fn drop(&mut self) {
+        for member in self.members.iter_mut().rev() {
+            if let Err(error) = member.destroy() {
+                log::error!("Failed to destroy group member: {}", error);
+            }
+        }
+
+        if self.unlink {
+            for member in self.members.iter_mut().rev() {
+                if let Err(error) = member.unlink() {
+                    log::error!("Failed to unlink group member: {}", error);
+                }
+            }
+        }
+
+        // Members are unmapped when `self.members` is dropped.
+    }
+}
+
+impl Teardown for Raw {
+    fn unlink(&mut self) -> crate::Result<()> {
+        Raw::unlink(self)
+    }
+}
+
+impl<T: 'static> Teardown for Shm<T> {
+    fn unlink(&mut self) -> crate::Result<()> {
+        Shm::unlink(self)
+    }
+}
+
+impl<T: 'static> Teardown for ShmSlice<T> {
+    fn unlink(&mut self) -> crate::Result<()> {
+        ShmSlice::unlink(self)
+    }
+}
@@ -0,0 +1,51 @@
+//! Non-cryptographic hashing shared by features that need a stable hash,
+//! such as schema hashes and name hashing.
+//!
+//! Hashes must agree across processes (and builds), so unlike
+//! `std::hash::DefaultHasher` the default here is fixed and unseeded.
+
+/// Default hash: the 64-bit FxHash mixing function, reading input as
+/// little-endian words so results don't depend on the host byte order.
+pub struct Fx;
+
+impl Fx {
+    const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+    pub const fn hash(bytes: &[u8]) -> u64 {
+        let mut hash = 0;
+        let mut index = 0;
+
+        while index + 8 <= bytes.len() {
+            let word = u64::from_le_bytes([
+                bytes[index],
+                bytes[index + 1],
+                bytes[index + 2],
+                bytes[index + 3],
+                bytes[index + 4],
+                bytes[index + 5],
+                bytes[index + 6],
+                bytes[index + 7],
+            ]);
+            hash = Self::mix(hash, word);
+            index += 8;
+        }
+
+        while index < bytes.len() {
+            hash = Self::mix(hash, bytes[index] as u64);
+            index += 1;
+        }
+
+        // Distinguish inputs that differ only in trailing zero bytes.
+        Self::mix(hash, bytes.len() as u64)
+    }
+
+    const fn mix(hash: u64, word: u64) -> u64 {
+        (hash.rotate_left(5) ^ word).wrapping_mul(Self::SEED)
+    }
+}
+
+/// Hash `bytes` with the default hash. Usable in `const` contexts, e.g.
+/// `const SCHEMA: u64 = shm::hash::hash(b"Point { x: u64, y: u64 }");`.
+pub const fn hash(bytes: &[u8]) -> u64 {
+    Fx::hash(bytes)
+}
@@ -0,0 +1,156 @@
+use core::sync::atomic::AtomicU8;
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
+use core::time::Duration;
+
+use crate::Clock;
+
+// How long attachers wait for the creator to publish the header before assuming
+// it never will, e.g. because it died or was created without one.
+static ATTACH_TIMEOUT_NANOS: AtomicU64 = AtomicU64::new(5_000_000_000);
+
+/// Configure how long attaching processes wait for the creator of a segment
+/// to publish its header, measured against the configured [`Clock`].
+/// Defaults to 5 seconds.
+pub fn set_attach_timeout(timeout: Duration) {
+    let nanos = u64::try_from(timeout.as_nanos()).unwrap_or(u64::MAX);
+    ATTACH_TIMEOUT_NANOS.store(nanos, Ordering::Relaxed);
+}
+
+fn attach_timeout() -> Duration {
+    Duration::from_nanos(ATTACH_TIMEOUT_NANOS.load(Ordering::Relaxed))
+}
+
+/// Metadata stored in the first page of segments created with a header,
+/// so attaching processes can check compatibility before touching the contents.
+#[repr(C)]
+pub(crate) struct Header {
+    // Written last by the creator: zero until the rest of the header is valid.
+    magic: AtomicU32,
+    endianness: AtomicU8,
+    pointer_width: AtomicU8,
+    // Caller-provided hash of the contents' layout, or zero if none.
+    schema: AtomicU64,
+    // Initialization state of the contents: one of `UNINIT`, `INITIALIZING`, or `READY`.
+    state: AtomicU8,
+    // High-water mark of initialized bytes, for incrementally filled contents.
+    initialized: AtomicU64,
+    // Number of elements in slice segments, written by the creator before publishing.
+    len: AtomicU64,
+}
+
+impl Header {
+    const MAGIC: u32 = u32::from_ne_bytes(*b"shm\0");
+
+    const BIG_ENDIAN: u8 = cfg!(target_endian = "big") as u8;
+    const POINTER_WIDTH: u8 = usize::BITS as u8;
+
+    const UNINIT: u8 = 0;
+    const INITIALIZING: u8 = 1;
+    const READY: u8 = 2;
+
+    /// Called once by the creator of the segment.
+    pub(crate) fn publish(&self, schema: Option<u64>) {
+        self.endianness.store(Self::BIG_ENDIAN, Ordering::Relaxed);
+        self.schema.store(schema.unwrap_or(0), Ordering::Relaxed);
+        self.pointer_width
+            .store(Self::POINTER_WIDTH, Ordering::Relaxed);
+        self.magic.store(Self::MAGIC, Ordering::Release);
+    }
+
+    /// Called by attaching processes. Blocks until the creator has published the header,
+    /// failing with `Error::MissingHeader` if it doesn't within [`set_attach_timeout`].
+    pub(crate) fn validate(&self, schema: Option<u64>) -> crate::Result<()> {
+        let clock = Clock::current();
+        let deadline = clock.deadline(attach_timeout())?;
+        while !self.is_published() {
+            if clock.is_past(&deadline)? {
+                return Err(crate::Error::MissingHeader);
+            }
+            std::thread::yield_now();
+        }
+
+        // Single bytes, so readable regardless of byte order.
+        let big_endian = self.endianness.load(Ordering::Relaxed);
+        let pointer_width = self.pointer_width.load(Ordering::Relaxed);
+
+        if big_endian != Self::BIG_ENDIAN || pointer_width != Self::POINTER_WIDTH {
+            return Err(crate::Error::ArchMismatch {
+                big_endian: big_endian != 0,
+                pointer_width,
+            });
+        }
+
+        if let Some(expected) = schema {
+            let actual = self.schema.load(Ordering::Relaxed);
+            if actual != expected {
+                return Err(crate::Error::SchemaMismatch { expected, actual });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn is_published(&self) -> bool {
+        self.magic.load(Ordering::Acquire) != 0
+    }
+
+    /// Returns `true` for the single caller that moves the state from uninitialized
+    /// to initializing.
+    pub(crate) fn claim(&self) -> bool {
+        self.state
+            .compare_exchange(
+                Self::UNINIT,
+                Self::INITIALIZING,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .is_ok()
+    }
+
+    /// Give up a claim without initializing, so another process can claim it.
+    pub(crate) fn abandon(&self) {
+        self.state.store(Self::UNINIT, Ordering::Release);
+    }
+
+    pub(crate) fn is_ready(&self) -> bool {
+        self.state.load(Ordering::Acquire) == Self::READY
+    }
+
+    pub(crate) fn set_ready(&self) {
+        self.state.store(Self::READY, Ordering::Release);
+    }
+
+    /// Wait for the contents to be initialized, failing with `Error::InitTimeout`
+    /// if they aren't within [`set_attach_timeout`], e.g. because the initializing
+    /// process died.
+    pub(crate) fn wait_ready(&self) -> crate::Result<()> {
+        let clock = Clock::current();
+        let deadline = clock.deadline(attach_timeout())?;
+        while !self.is_ready() {
+            if clock.is_past(&deadline)? {
+                return Err(crate::Error::InitTimeout);
+            }
+            std::thread::yield_now();
+        }
+        Ok(())
+    }
+
+    pub(crate) fn initialized(&self) -> u64 {
+        self.initialized.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn set_initialized(&self, len: u64) {
+        self.initialized.fetch_max(len, Ordering::Release);
+    }
+
+    pub(crate) fn len(&self) -> u64 {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Must be called before [`Header::publish`].
+    pub(crate) fn set_len(&self, len: u64) {
+        self.len.store(len, Ordering::Relaxed);
+    }
+}
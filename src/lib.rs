@@ -1,25 +1,88 @@
 use core::marker::PhantomData;
 use core::mem;
+use core::mem::MaybeUninit;
 use core::num::NonZeroUsize;
+use core::ops::Deref;
+use core::ops::DerefMut;
 use core::ptr::NonNull;
+use core::slice;
+use core::sync::atomic;
+use core::sync::atomic::Ordering;
+use std::os::fd::OwnedFd;
 
+mod arena;
 pub mod backend;
-mod barrier;
+pub mod barrier;
+mod bitmap;
+pub mod cleanup;
+mod clock;
+mod condvar;
+mod deque;
+mod epoch;
 mod error;
+mod event;
+mod group;
+pub mod hash;
+mod header;
+mod mapped_slice;
+mod mutex;
 mod numa;
+mod prefault;
 mod raw;
 mod reservation;
+mod ring;
+mod rwlock;
+mod semaphore;
+mod spinlock;
+mod spsc;
+#[cfg(all(test, feature = "builder"))]
+mod testing;
 
+pub use arena::Arena;
 pub use backend::Backend;
+pub use backend::set_auto_thp_threshold;
 pub use barrier::Barrier;
+pub use bitmap::Bitmap;
+pub use clock::Clock;
+pub use clock::set_clock;
+pub use condvar::CondVar;
+pub use deque::Deque;
+pub use epoch::Epoch;
 pub use error::Error;
+pub use error::ErrorKind;
+pub use event::Event;
+pub use group::ShmGroup;
+pub use group::Teardown;
+pub use header::set_attach_timeout;
+pub use mapped_slice::MappedSlice;
+pub use mutex::LockResult;
+pub use mutex::Mutex;
+pub use mutex::MutexGuard;
+pub use numa::MbindFlags;
+pub use numa::Migration;
 pub use numa::Numa;
+pub use numa::Topology;
+pub use numa::topology;
+pub use prefault::configure_prefault_pool;
+pub use prefault::shutdown_prefault_pool;
 pub use raw::Raw;
+pub use raw::SegmentIdentity;
 pub use reservation::Reservation;
+pub use ring::Log;
+pub use ring::LogIter;
+pub use rwlock::RwLock;
+pub use rwlock::RwLockReadGuard;
+pub use rwlock::RwLockWriteGuard;
+pub use semaphore::Semaphore;
+pub use spinlock::SpinLock;
+pub use spinlock::SpinLockGuard;
+pub use spsc::SpscConsumer;
+pub use spsc::SpscProducer;
+pub use spsc::SpscRing;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-use bon::bon;
+use crate::header::Header;
 
 #[repr(C, align(4096))]
 pub struct Page([u8; 4096]);
@@ -28,59 +91,987 @@ impl Page {
     pub const SIZE: usize = mem::size_of::<Self>();
 }
 
+/// 2 MiB huge page, e.g. for sizing mappings backed by [`HugePage::Huge2MB`].
+#[repr(C, align(2097152))]
+pub struct HugePage2M([u8; 1 << 21]);
+
+impl HugePage2M {
+    pub const SIZE: usize = mem::size_of::<Self>();
+}
+
 #[derive(Copy, Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Populate {
     PageTable,
+    /// Like `PageTable`, but only read ahead the first `pages` pages with
+    /// `MADV_WILLNEED` instead of populating the whole mapping with `MAP_POPULATE`,
+    /// which performs synchronous readahead of the entire file for file-backed mappings.
+    Prefix {
+        pages: usize,
+    },
     Physical,
+    /// Like `Physical`, but also lock the pages into memory with `mlock2` so
+    /// they cannot be swapped out, e.g. for real-time buffers. See [`Raw::lock`].
+    Lock,
+}
+
+/// Size of huge pages backing a mapping, via `MAP_HUGETLB`.
+///
+/// Anonymous mappings request huge pages directly. File-backed mappings must
+/// instead live on a hugetlbfs mount (e.g. via [`backend::FileBacked`]), where
+/// this only rounds the size up to the huge page boundary. Mapping other files
+/// fails with `Error::HugePageUnsupported`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum HugePage {
+    #[default]
+    None,
+    Huge2MB,
+    Huge1GB,
+}
+
+impl HugePage {
+    /// Size of the pages backing the mapping, which sizes are rounded up to.
+    pub const fn size(&self) -> usize {
+        match self {
+            HugePage::None => Page::SIZE,
+            HugePage::Huge2MB => HugePage2M::SIZE,
+            // Exceeds the maximum alignment of `repr(align)`, so has no page type.
+            HugePage::Huge1GB => 1 << 30,
+        }
+    }
+
+    pub(crate) fn flags(&self) -> libc::c_int {
+        match self {
+            HugePage::None => 0,
+            HugePage::Huge2MB => libc::MAP_HUGETLB | libc::MAP_HUGE_2MB,
+            HugePage::Huge1GB => libc::MAP_HUGETLB | libc::MAP_HUGE_1GB,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Protection {
+    ReadOnly,
+    #[default]
+    ReadWrite,
+    /// Executable mappings, e.g. for code caches shared across processes.
+    /// See [`Raw::flush_icache`].
+    ///
+    /// Note: fails with `EPERM` if the backing filesystem is mounted `noexec`,
+    /// as `/dev/shm` commonly is.
+    ReadExec,
+    ReadWriteExec,
+}
+
+impl Protection {
+    pub(crate) fn prot(&self) -> libc::c_int {
+        match self {
+            Protection::ReadOnly => libc::PROT_READ,
+            Protection::ReadWrite => libc::PROT_READ | libc::PROT_WRITE,
+            Protection::ReadExec => libc::PROT_READ | libc::PROT_EXEC,
+            Protection::ReadWriteExec => libc::PROT_READ | libc::PROT_WRITE | libc::PROT_EXEC,
+        }
+    }
+}
+
+/// Access pattern hint for an existing mapping. See [`Raw::advise`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Advice {
+    /// Expect sequential access, so read ahead aggressively.
+    Sequential,
+    /// Expect random access, so don't read ahead.
+    Random,
+    /// Expect access soon, so read ahead now.
+    WillNeed,
+    /// Expect no access soon, so the kernel may free the pages.
+    ///
+    /// Note: pages of a shared mapping are not discarded from the backing
+    /// object, so their contents survive.
+    DontNeed,
+    /// Back the mapping with transparent huge pages where possible.
+    HugePage,
+    NoHugePage,
+}
+
+impl Advice {
+    pub(crate) fn advice(&self) -> libc::c_int {
+        match self {
+            Advice::Sequential => libc::MADV_SEQUENTIAL,
+            Advice::Random => libc::MADV_RANDOM,
+            Advice::WillNeed => libc::MADV_WILLNEED,
+            Advice::DontNeed => libc::MADV_DONTNEED,
+            Advice::HugePage => libc::MADV_HUGEPAGE,
+            Advice::NoHugePage => libc::MADV_NOHUGEPAGE,
+        }
+    }
+}
+
+/// Whether a process created a segment or attached to an existing one.
+/// See [`Shm::role`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SegmentRole {
+    Creator,
+    Attacher,
 }
 
 pub struct Shm<T> {
     inner: Raw,
+    header: bool,
     r#type: PhantomData<T>,
 }
 
-#[bon]
+#[cfg(feature = "builder")]
+#[bon::bon]
 impl<T> Shm<T> {
     #[builder]
     pub fn new(
         numa: Option<Numa>,
+        /// Flags for applying `numa`. See [`MbindFlags`].
+        #[builder(default)]
+        mbind_flags: MbindFlags,
+        name: String,
+        #[builder(default)] create: bool,
+        /// Only open an existing segment, failing with `ENOENT` instead of creating
+        /// one. See [`Shm::open`].
+        #[builder(default)]
+        attach: bool,
+        populate: Option<Populate>,
+        /// Reserve the first page of the segment for a header recording the
+        /// creator's architecture, which is validated by attaching processes.
+        #[builder(default)]
+        header: bool,
+        /// Hash of `T`'s layout, stored in the header by the creator and validated by
+        /// attaching processes. Unlike the size, this catches same-size layout changes
+        /// such as reordered fields. Implies `header`.
+        schema: Option<u64>,
+        /// Defaults to [`Backend::Shm`].
+        #[builder(into)]
+        backend: Option<Backend>,
+        /// Permission bits for a newly created shm object, e.g. `0o600` for segments
+        /// holding sensitive data on multi-user hosts. Defaults to `0o666`, masked by
+        /// the process umask. Ignored when attaching, or for other backends.
+        mode: Option<libc::mode_t>,
+        /// Constructor for `T`, run exactly once by the process that creates the
+        /// segment, which then marks it initialized. Attaching processes that pass
+        /// `init` never run it, and instead wait until the creator has finished,
+        /// so they don't clobber or observe partially initialized contents. Implies `header`.
+        ///
+        /// Note: a process passing `create: true` first unlinks any existing segment,
+        /// so if two processes race to create, both may run `init` on separate
+        /// segments. Exactly one process should pass `create: true`; the others
+        /// should attach with `create: false`, in which case whichever process
+        /// actually creates the segment wins.
+        init: Option<&mut dyn FnMut(&mut MaybeUninit<T>)>,
+        /// Hold an exclusive `flock` on the backing file while the creator publishes
+        /// the header and runs `init`, so attaching processes block in the kernel
+        /// instead of spinning until it finishes. Implies `header`. Has no effect
+        /// for anonymous mappings.
+        #[builder(default)]
+        coordinate_init: bool,
+        #[builder(default)] huge_page: HugePage,
+        /// Defaults to the access the segment was opened with. Passing
+        /// [`Protection::ReadOnly`] maps only `PROT_READ` even if the backend opened
+        /// the segment for writing, e.g. to attach a subscriber that must not mutate
+        /// it. Creating processes need write access to initialize the segment.
+        ///
+        /// See [`Shm::into_readonly`] to also drop mutable access from the type.
+        protection: Option<Protection>,
+        /// Unlink the segment when dropped, if this process created it, so that
+        /// attaching processes dropping their handles leave it in place.
+        #[builder(default)]
+        unlink_on_drop: bool,
+        /// Map at exactly this address, e.g. so that pointers stored in the segment
+        /// are valid in every process. Fails with `EEXIST` if any existing mapping
+        /// overlaps the range, rather than silently replacing it.
+        address: Option<NonNull<Page>>,
+        /// Keep the backing file descriptor open across `exec`, e.g. to pass the
+        /// segment to a child process. By default, it is closed on `exec`.
+        #[builder(default)]
+        inheritable: bool,
+    ) -> crate::Result<Self> {
+        let header = header || schema.is_some() || init.is_some() || coordinate_init;
+        Self::with_header(
+            name,
+            raw::Options {
+                create,
+                attach,
+                numa,
+                mbind_flags,
+                populate,
+                unlink_on_drop,
+                backend,
+                mode,
+                protection,
+                huge_page,
+                address,
+                inheritable,
+            },
+            header,
+            schema,
+            init,
+            coordinate_init,
+        )
+    }
+
+    /// Shared slice of `T` whose length is only known at runtime. See [`ShmSlice`].
+    #[builder]
+    pub fn new_slice(
         name: String,
         #[builder(default)] create: bool,
+        /// Number of elements, which the creator must provide, or fail with
+        /// `Error::MissingLength`. Attachers read it from the segment's header instead.
+        len: Option<usize>,
+        numa: Option<Numa>,
         populate: Option<Populate>,
+        /// Defaults to [`Backend::Shm`].
+        #[builder(into)]
+        backend: Option<Backend>,
+    ) -> crate::Result<ShmSlice<T>> {
+        Self::slice_with_options(
+            name,
+            len,
+            raw::Options {
+                create,
+                attach: !create,
+                numa,
+                populate,
+                backend,
+                ..raw::Options::default()
+            },
+        )
+    }
+}
+
+impl<T> Shm<T> {
+    const SIZE: usize = mem::size_of::<T>().next_multiple_of(Page::SIZE);
+
+    /// Size of the mapping holding a `T`, rounded up to a multiple of the page size.
+    /// Segments created with a header map one additional page.
+    pub const fn mapped_size() -> usize {
+        Self::SIZE
+    }
+
+    /// Alignment of the mapping holding a `T`.
+    pub const fn alignment() -> usize {
+        Page::SIZE
+    }
+
+    // Map a segment without a header, like `Shm::builder()` with only these options.
+    pub(crate) fn with_options(name: String, options: raw::Options) -> crate::Result<Self> {
+        Self::check_alignment()?;
+        Raw::with_options(name, Self::SIZE, options).map(|inner| Self {
+            inner,
+            header: false,
+            r#type: PhantomData,
+        })
+    }
+
+    // Shared by `Shm::builder()` and the plain constructors that need a header.
+    fn with_header(
+        name: String,
+        options: raw::Options,
+        header: bool,
+        schema: Option<u64>,
+        init: Option<&mut dyn FnMut(&mut MaybeUninit<T>)>,
+        coordinate_init: bool,
+    ) -> crate::Result<Self> {
+        Self::check_alignment()?;
+
+        let size = Self::SIZE + if header { Page::SIZE } else { 0 };
+        let inner = Raw::with_options(name, size, options)?;
+
+        let shm = Self {
+            inner,
+            header,
+            r#type: PhantomData,
+        };
+
+        let create = shm.role() == SegmentRole::Creator;
+        if coordinate_init {
+            match create {
+                true => shm.inner.flock(libc::LOCK_EX)?,
+                false => shm.wait_unlocked(init.is_some())?,
+            }
+        }
+
+        if let Some(header) = shm.header() {
+            match create {
+                true => header.publish(schema),
+                false => header.validate(schema)?,
+            }
+        }
+
+        if let (Some(init), Some(header)) = (init, shm.header()) {
+            match create {
+                true => {
+                    init(unsafe { shm.address().cast::<MaybeUninit<T>>().as_mut() });
+                    header.set_ready();
+                }
+                false => header.wait_ready()?,
+            }
+        }
+
+        if coordinate_init && create {
+            shm.inner.flock(libc::LOCK_UN)?;
+        }
+
+        Ok(shm)
+    }
+
+    // Shared by `Shm::new_slice()` and the plain slice constructors.
+    fn slice_with_options(
+        name: String,
+        len: Option<usize>,
+        options: raw::Options,
+    ) -> crate::Result<ShmSlice<T>> {
+        assert!(
+            size_of::<T>() > 0,
+            "ShmSlice does not support zero-sized types"
+        );
+        Self::check_alignment()?;
+
+        let create = options.create;
+
+        // Attachers map the header page, and the backend maps the rest of the
+        // existing segment along with it.
+        let size = match (create, len) {
+            (true, None) => return Err(crate::Error::MissingLength),
+            (true, Some(len)) => len
+                .checked_mul(size_of::<T>())
+                .and_then(|size| size.checked_add(Page::SIZE))
+                .ok_or(crate::Error::LengthOverflow { len })?,
+            (false, _) => Page::SIZE,
+        };
+
+        let inner = Raw::with_options(name, size, options)?;
+
+        let header = unsafe { inner.address.cast::<Header>().as_ref() };
+        let len = match create {
+            true => {
+                let len = len.unwrap();
+                header.set_len(len as u64);
+                header.publish(None);
+                len
+            }
+            false => {
+                header.validate(None)?;
+                let len = header.len() as usize;
+                inner.file.validate_size(
+                    len.saturating_mul(size_of::<T>())
+                        .saturating_add(Page::SIZE),
+                )?;
+                len
+            }
+        };
+
+        Ok(ShmSlice {
+            inner,
+            len,
+            r#type: PhantomData,
+        })
+    }
+
+    // Mappings are only page-aligned, so over-aligned types would be misaligned.
+    fn check_alignment() -> crate::Result<()> {
+        match align_of::<T>() <= Self::alignment() {
+            true => Ok(()),
+            false => Err(crate::Error::Alignment {
+                required: align_of::<T>(),
+                actual: Self::alignment(),
+            }),
+        }
+    }
+
+    /// Create a fresh segment, unlinking any stale segment with the same name.
+    /// Shorthand for `Shm::builder().name(name).create(true).build()`.
+    pub fn create(name: String) -> crate::Result<Self> {
+        Self::with_options(
+            name,
+            raw::Options {
+                create: true,
+                ..raw::Options::default()
+            },
+        )
+    }
+
+    /// Attach to an existing segment, failing if it doesn't exist or is too
+    /// small to hold a `T` (e.g. because the creator disagrees about `T`'s layout).
+    pub fn open(name: String) -> crate::Result<Self> {
+        let shm = Self::with_options(
+            name,
+            raw::Options {
+                attach: true,
+                ..raw::Options::default()
+            },
+        )?;
+        shm.inner.file.validate_size(Self::SIZE)?;
+        Ok(shm)
+    }
+
+    /// Create a fresh segment with a header, whose contents are initialized by `init`
+    /// before attaching processes observe them. Attachers validate `schema`, if any.
+    pub fn create_init(
+        name: String,
+        schema: Option<u64>,
+        mut init: impl FnMut(&mut MaybeUninit<T>),
     ) -> crate::Result<Self> {
-        let inner = Raw::builder()
-            .maybe_numa(numa)
-            .name(name)
-            .size(Self::SIZE)
-            .create(create)
-            .maybe_populate(populate)
-            .build()?;
-
-        Ok(Self {
+        Self::with_header(
+            name,
+            raw::Options {
+                create: true,
+                ..raw::Options::default()
+            },
+            true,
+            schema,
+            Some(&mut init),
+            false,
+        )
+    }
+
+    /// Attach to an existing segment created by [`Shm::create_init`], waiting until
+    /// its contents are initialized.
+    pub fn open_init(name: String, schema: Option<u64>) -> crate::Result<Self> {
+        let shm = Self::with_header(
+            name,
+            raw::Options {
+                attach: true,
+                ..raw::Options::default()
+            },
+            true,
+            schema,
+            None,
+            false,
+        )?;
+        shm.inner.file.validate_size(Self::SIZE + Page::SIZE)?;
+        shm.wait_ready()?;
+        Ok(shm)
+    }
+
+    /// Create a fresh [`ShmSlice`] of `len` elements, unlinking any stale segment
+    /// with the same name.
+    pub fn create_slice(name: String, len: usize) -> crate::Result<ShmSlice<T>> {
+        Self::slice_with_options(
+            name,
+            Some(len),
+            raw::Options {
+                create: true,
+                ..raw::Options::default()
+            },
+        )
+    }
+
+    /// Attach to an existing [`ShmSlice`], reading its length from the header.
+    pub fn open_slice(name: String) -> crate::Result<ShmSlice<T>> {
+        Self::slice_with_options(
+            name,
+            None,
+            raw::Options {
+                attach: true,
+                ..raw::Options::default()
+            },
+        )
+    }
+
+    /// Like [`Shm::open`], but returns `None` if the segment doesn't exist yet,
+    /// e.g. when polling for a producer's segment to appear.
+    pub fn try_open(name: String) -> crate::Result<Option<Self>> {
+        match Self::open(name) {
+            Ok(shm) => Ok(Some(shm)),
+            Err(error) if error.is_not_found() => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Create a segment with a random name starting with `prefix`, which is
+    /// unlinked when dropped. Useful for throwaway segments in tests and
+    /// short-lived handlers.
+    pub fn ephemeral(prefix: &str) -> crate::Result<Self> {
+        loop {
+            let name = format!("{prefix}{:016x}", random()?);
+            let mut shm = Self::with_options(name, raw::Options::default())?;
+
+            // Already existed: collisions are unlikely, but we must not take
+            // ownership of another process's segment.
+            if shm.role() == SegmentRole::Attacher {
+                log::warn!("Ephemeral shm name collision: {:?}", shm.inner.name);
+                continue;
+            }
+
+            shm.inner.unlink_on_drop = true;
+            return Ok(shm);
+        }
+    }
+
+    /// Map an externally created file descriptor (e.g. a dma-buf exported by a GPU driver)
+    /// as a typed segment, checking that the file is large enough to hold a `T`.
+    pub fn from_owned_fd(fd: OwnedFd, create: bool) -> crate::Result<Self> {
+        Self::check_alignment()?;
+        Raw::from_owned_fd(fd, mem::size_of::<T>(), create).map(|inner| Self {
+            inner,
+            header: false,
+            r#type: PhantomData,
+        })
+    }
+
+    /// Attach to an existing segment without write access.
+    /// See [`ReadOnlyShm`].
+    pub fn attach_readonly(name: String) -> crate::Result<ReadOnlyShm<T>> {
+        Self::check_alignment()?;
+        Raw::open_readonly(name, Self::SIZE).map(|inner| ReadOnlyShm {
             inner,
+            header: false,
+            r#type: PhantomData,
+        })
+    }
+
+    /// Revoke write access to the mapping, returning a handle that only exposes
+    /// shared references. See [`Raw::protect_readonly`].
+    pub fn into_readonly(mut self) -> crate::Result<ReadOnlyShm<T>> {
+        self.inner.protect_readonly()?;
+        Ok(ReadOnlyShm {
+            inner: self.inner,
+            header: self.header,
             r#type: PhantomData,
         })
     }
+
+    pub fn address(&self) -> NonNull<T> {
+        match self.header {
+            true => unsafe { self.inner.address.add(1) }.cast(),
+            false => self.inner.address.cast(),
+        }
+    }
+
+    /// Whether this process created the segment, e.g. to decide whether to
+    /// initialize its contents.
+    pub fn role(&self) -> SegmentRole {
+        self.inner.role()
+    }
+
+    pub fn as_ptr(&self) -> *const T {
+        self.address().as_ptr()
+    }
+
+    pub fn as_mut_ptr(&self) -> *mut T {
+        self.address().as_ptr()
+    }
+
+    /// # Safety
+    ///
+    /// The caller must ensure the mapping holds a valid `T`. Newly created
+    /// mappings are zeroed by the kernel, so this holds for types valid when
+    /// zeroed, or after the creator initializes the contents. Fields modified
+    /// concurrently by other processes should use interior mutability.
+    pub unsafe fn get(&self) -> &T {
+        unsafe { self.address().as_ref() }
+    }
+
+    /// # Safety
+    ///
+    /// See [`Shm::get`].
+    pub unsafe fn get_mut(&mut self) -> &mut T {
+        unsafe { self.address().as_mut() }
+    }
+
+    pub fn size(&self) -> NonZeroUsize {
+        self.inner.size
+    }
+
+    pub fn unlink(&mut self) -> crate::Result<()> {
+        self.inner.unlink()
+    }
+
+    /// Grow the segment to `size` bytes (including any header), returning the
+    /// possibly moved address. See [`Raw::grow`].
+    pub fn grow(&mut self, size: usize) -> crate::Result<NonNull<T>> {
+        self.inner.grow(size)?;
+        Ok(self.address())
+    }
+
+    /// See [`Raw::advise`].
+    pub fn advise(&self, advice: Advice) -> crate::Result<()> {
+        self.inner.advise(advice)
+    }
+
+    /// See [`Raw::resident_pages`]. Includes the header page, if any.
+    pub fn resident_pages(&self) -> crate::Result<(usize, usize)> {
+        self.inner.resident_pages()
+    }
+
+    /// See [`Raw::lock`].
+    pub fn lock(&self) -> crate::Result<()> {
+        self.inner.lock()
+    }
+
+    /// See [`Raw::unlock`].
+    pub fn unlock(&self) -> crate::Result<()> {
+        self.inner.unlock()
+    }
+
+    /// Zero the contents by releasing their physical pages, which is much cheaper
+    /// than writing zeros to a large segment. The header page, if any, is kept.
+    ///
+    /// Anonymous mappings are private, so `MADV_DONTNEED` suffices. For backends
+    /// with a file, `MADV_DONTNEED` would only drop this process's page table
+    /// entries and re-read the old contents, so this uses `MADV_REMOVE` to free
+    /// the backing pages instead, which zeroes the segment for every process
+    /// attached to it. Fails with `EOPNOTSUPP` on filesystems that can't punch holes.
+    pub fn reset(&mut self) -> crate::Result<()> {
+        let offset = if self.header { Page::SIZE } else { 0 };
+        let advice = match self.inner.as_fd() {
+            None => libc::MADV_DONTNEED,
+            Some(_) => libc::MADV_REMOVE,
+        };
+
+        crate::backend::madvise(
+            unsafe { self.inner.address.byte_add(offset) }
+                .as_ptr()
+                .cast(),
+            self.inner.size.get().next_multiple_of(Page::SIZE) - offset,
+            advice,
+        )
+    }
+
+    /// See [`Raw::flush`].
+    pub fn flush(&self) -> crate::Result<()> {
+        self.inner.flush()
+    }
+
+    /// See [`Raw::flush_async`].
+    pub fn flush_async(&self) -> crate::Result<()> {
+        self.inner.flush_async()
+    }
+
+    /// See [`Raw::flush_range`]. Offsets are relative to the start of the
+    /// mapping, including any header.
+    pub fn flush_range(&self, offset: usize, len: usize) -> crate::Result<()> {
+        self.inner.flush_range(offset, len)
+    }
+
+    /// Identity of the backing object. See [`Raw::identity`].
+    pub fn identity(&self) -> crate::Result<Option<SegmentIdentity>> {
+        self.inner.identity()
+    }
+
+    /// Whether the segment is mapped without write access. See [`Raw::is_readonly`].
+    pub fn is_readonly(&self) -> bool {
+        self.inner.is_readonly()
+    }
+
+    /// Map a private copy-on-write view of the segment. See [`Raw::private_view`].
+    pub fn private_view(&self) -> crate::Result<PrivateShm<T>> {
+        self.inner.private_view().map(|inner| PrivateShm {
+            inner: Shm {
+                inner,
+                header: self.header,
+                r#type: PhantomData,
+            },
+        })
+    }
+
+    /// Unmap the segment but leave it for other processes to attach to.
+    /// See [`Raw::detach`].
+    pub fn detach(self) {
+        self.inner.detach()
+    }
+
+    /// Whether the creator has called [`Shm::set_initialized`]. Never blocks, so
+    /// callers can poll this before deciding to attach or wait.
+    ///
+    /// Always `false` for segments created without a header.
+    pub fn is_initialized(&self) -> bool {
+        self.header().is_some_and(Header::is_ready)
+    }
+
+    /// Mark the contents as initialized for other processes, with release
+    /// ordering. No-op for segments created without a header.
+    pub fn set_initialized(&self) {
+        if let Some(header) = self.header() {
+            header.set_ready();
+        }
+    }
+
+    /// Advance the high-water mark of initialized bytes to `len`, with release
+    /// ordering, so readers know how much of an incrementally filled segment is
+    /// safe to read. The mark never moves backward. No-op without a header.
+    pub fn set_initialized_len(&self, len: usize) {
+        if let Some(header) = self.header() {
+            header.set_initialized(len as u64);
+        }
+    }
+
+    /// High-water mark set by [`Shm::set_initialized_len`], with acquire ordering,
+    /// or `None` for segments created without a header.
+    pub fn initialized_len(&self) -> Option<usize> {
+        self.header().map(|header| header.initialized() as usize)
+    }
+
+    /// Try to claim the right to initialize the contents. Exactly one caller across
+    /// all attached processes receives an [`InitGuard`], and must call
+    /// [`InitGuard::finish`] once done; everyone else receives `None` and should
+    /// call [`Shm::wait_ready`].
+    ///
+    /// Fails with `Error::MissingHeader` for segments created without a header.
+    pub fn claim_init(&self) -> crate::Result<Option<InitGuard<'_, T>>> {
+        let header = self.header().ok_or(crate::Error::MissingHeader)?;
+        Ok(header.claim().then_some(InitGuard { shm: self }))
+    }
+
+    /// Block until the contents are initialized, via [`Shm::set_initialized`] or
+    /// [`InitGuard::finish`].
+    ///
+    /// Fails with `Error::MissingHeader` for segments created without a header,
+    /// and `Error::InitTimeout` if the contents aren't initialized in time.
+    pub fn wait_ready(&self) -> crate::Result<()> {
+        self.header()
+            .ok_or(crate::Error::MissingHeader)?
+            .wait_ready()
+    }
+
+    // Block until the creator has released its `flock`, retrying if this process
+    // took the lock first, i.e. between the creator opening and locking the file.
+    fn wait_unlocked(&self, init: bool) -> crate::Result<()> {
+        let header = self.header().unwrap();
+        loop {
+            self.inner.flock(libc::LOCK_SH)?;
+            let done = header.is_published() && (!init || header.is_ready());
+            self.inner.flock(libc::LOCK_UN)?;
+
+            if done {
+                return Ok(());
+            }
+
+            std::thread::yield_now();
+        }
+    }
+
+    fn header(&self) -> Option<&Header> {
+        match self.header {
+            true => Some(unsafe { self.inner.address.cast::<Header>().as_ref() }),
+            false => None,
+        }
+    }
 }
 
-impl<T> Shm<T> {
-    const SIZE: usize = mem::size_of::<T>().next_multiple_of(Page::SIZE);
+impl<T: Copy> Shm<T> {
+    /// Publish `value` with a volatile write of the whole value, ordered after
+    /// all preceding writes by a release fence.
+    ///
+    /// Note: values larger than a machine word may be observed torn by concurrent
+    /// readers, who must synchronize separately (e.g. with a seqlock).
+    pub fn store(&self, value: T) {
+        atomic::fence(Ordering::Release);
+        unsafe { self.address().write_volatile(value) }
+    }
+
+    /// Read the whole value with a volatile read, ordered before all subsequent
+    /// reads by an acquire fence. See [`Shm::store`].
+    pub fn load(&self) -> T {
+        let value = unsafe { self.address().read_volatile() };
+        atomic::fence(Ordering::Acquire);
+        value
+    }
+}
+
+/// Safe access for types valid for any bit pattern, including the zeroed
+/// contents of a newly created mapping.
+#[cfg(feature = "bytemuck")]
+impl<T: bytemuck::Pod> Deref for Shm<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.get() }
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+impl<T: bytemuck::Pod> DerefMut for Shm<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.get_mut() }
+    }
+}
+
+/// Read-only view of a segment created by another process, e.g. for monitoring.
+///
+/// The segment is mapped `PROT_READ`, either opened `O_RDONLY` by [`Shm::attach_readonly`]
+/// or with write access revoked by [`Shm::into_readonly`], and this type only
+/// derefs to `&T`, so an observer cannot corrupt the producer's state even by bug.
+/// Fields modified concurrently by the producer should use interior mutability.
+pub struct ReadOnlyShm<T> {
+    inner: Raw,
+    header: bool,
+    r#type: PhantomData<T>,
+}
+
+impl<T> ReadOnlyShm<T> {
+    pub fn address(&self) -> NonNull<T> {
+        match self.header {
+            true => unsafe { self.inner.address.add(1) }.cast(),
+            false => self.inner.address.cast(),
+        }
+    }
+
+    pub fn size(&self) -> NonZeroUsize {
+        self.inner.size
+    }
+}
+
+impl<T> Deref for ReadOnlyShm<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.address().as_ref() }
+    }
+}
+
+/// Private copy-on-write view of a segment, returned by [`Shm::private_view`],
+/// e.g. to try out modifications and discard them without touching the shared copy.
+pub struct PrivateShm<T> {
+    inner: Shm<T>,
+}
 
+impl<T> PrivateShm<T> {
     pub fn address(&self) -> NonNull<T> {
-        self.inner.address.cast()
+        self.inner.address()
     }
 
+    pub fn size(&self) -> NonZeroUsize {
+        self.inner.size()
+    }
+}
+
+impl<T> Deref for PrivateShm<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.address().as_ref() }
+    }
+}
+
+impl<T> DerefMut for PrivateShm<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: pages are private to this mapping once written.
+        unsafe { self.address().as_mut() }
+    }
+}
+
+/// Shared slice of `T` whose length is only known at runtime, created by
+/// [`Shm::new_slice`].
+///
+/// The creator stores the length in a header page preceding the elements,
+/// so attaching processes learn the length without being told.
+pub struct ShmSlice<T> {
+    inner: Raw,
+    len: usize,
+    r#type: PhantomData<T>,
+}
+
+unsafe impl<T: Sync> Sync for ShmSlice<T> {}
+unsafe impl<T: Send> Send for ShmSlice<T> {}
+
+impl<T> ShmSlice<T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn address(&self) -> NonNull<T> {
+        unsafe { self.inner.address.add(1) }.cast()
+    }
+
+    /// Size of the mapping, including the header page.
     pub fn size(&self) -> NonZeroUsize {
         self.inner.size
     }
 
+    /// # Safety
+    ///
+    /// See [`Shm::get`].
+    pub unsafe fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.address().as_ptr(), self.len) }
+    }
+
+    /// # Safety
+    ///
+    /// See [`Shm::get`].
+    pub unsafe fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.address().as_ptr(), self.len) }
+    }
+
     pub fn unlink(&mut self) -> crate::Result<()> {
         self.inner.unlink()
     }
 }
 
+/// Exclusive right to initialize a segment, returned by [`Shm::claim_init`].
+///
+/// Dropping the guard without calling [`InitGuard::finish`] (e.g. on panic)
+/// releases the claim, so another process can retry initialization.
+pub struct InitGuard<'shm, T> {
+    shm: &'shm Shm<T>,
+}
+
+impl<T> InitGuard<'_, T> {
+    /// Mark the contents as initialized for other processes, with release ordering.
+    pub fn finish(self) {
+        self.header().set_ready();
+        mem::forget(self);
+    }
+
+    fn header(&self) -> &Header {
+        self.shm.header().unwrap()
+    }
+}
+
+impl<T> Deref for InitGuard<'_, T> {
+    type Target = MaybeUninit<T>;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.shm.address().cast::<MaybeUninit<T>>().as_ref() }
+    }
+}
+
+impl<T> DerefMut for InitGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: only the guard holder may access the contents until `finish`.
+        unsafe { self.shm.address().cast::<MaybeUninit<T>>().as_mut() }
+    }
+}
+
+impl<T> Drop for InitGuard<'_, T> {
+    fn drop(&mut self) {
+        self.header().abandon();
+    }
+}
+
+impl<const N: usize> Shm<[Page; N]> {
+    /// Map `N` shared pages of scratch space without defining a wrapper type.
+    pub fn pages(name: String) -> crate::Result<Self> {
+        Self::with_options(name, raw::Options::default())
+    }
+
+    pub fn pages_mut(&mut self) -> &mut [Page] {
+        // SAFETY: mapping is `N` pages long, and `Page` is valid for any bit pattern.
+        unsafe { self.address().as_mut() }
+    }
+}
+
+fn random() -> crate::Result<u64> {
+    let mut bytes = [0u8; 8];
+    unsafe { crate::try_libc!(libc::getrandom(bytes.as_mut_ptr().cast(), bytes.len(), 0)) }?;
+    Ok(u64::from_ne_bytes(bytes))
+}
+
 macro_rules! try_libc {
     // mmap64 returns a pointer instead of a status code
     (libc::mmap64( $($arg:expr),* $(,)? )) => {
@@ -93,13 +1084,35 @@ macro_rules! try_libc {
         }
     };
 
+    // mremap also returns a pointer
+    (libc::mremap( $($arg:expr),* $(,)? )) => {
+        match libc::mremap ( $($arg),* ) {
+            libc::MAP_FAILED => Err(crate::Error::Libc {
+                name: "mremap",
+                source: ::std::io::Error::last_os_error()
+            }),
+            value => Ok(value),
+        }
+    };
+
+    // sem_open also returns a pointer
+    (libc::sem_open( $($arg:expr),* $(,)? )) => {
+        match libc::sem_open ( $($arg),* ) {
+            libc::SEM_FAILED => Err(crate::Error::Libc {
+                name: "sem_open",
+                source: ::std::io::Error::last_os_error()
+            }),
+            value => Ok(value),
+        }
+    };
+
     (libc:: $function:ident ( $($arg:expr),* $(,)? )) => {
         {
             use libc::$function;
             crate::try_libc!($function ( $($arg),* ))
         }
     };
-    // Only needed for `mbind_syscall` in raw.rs
+    // Only needed for raw syscall helpers like `mbind_syscall` in numa.rs
     ($function:ident ( $($arg:expr),* $(,)? )) => {
         match $function ( $($arg),* ) {
             -1 => Err(crate::Error::Libc {
@@ -121,6 +1134,17 @@ macro_rules! try_pthread {
             }),
         }
     };
+
+    // Only needed for glibc extensions missing from `libc`
+    ($function:ident ( $($arg:expr),* $(,)? )) => {
+        match $function ( $($arg),* ) {
+            0 => Ok(()),
+            error => Err(crate::Error::Libc {
+                name: stringify!($function),
+                source: std::io::Error::from_raw_os_error(error),
+            }),
+        }
+    };
 }
 
 pub(crate) use try_libc;
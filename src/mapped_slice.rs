@@ -0,0 +1,127 @@
+use core::marker::PhantomData;
+use core::slice;
+
+#[cfg(feature = "builder")]
+use crate::Page;
+use crate::Raw;
+#[cfg(feature = "builder")]
+use crate::header::Header;
+
+/// Shared slice of `T` whose length is stored in a header by the creator,
+/// so attaching processes learn the length without being told.
+///
+/// The header occupies the first page, followed by the elements.
+pub struct MappedSlice<T> {
+    inner: Raw,
+    len: usize,
+    r#type: PhantomData<T>,
+}
+
+unsafe impl<T: Sync> Sync for MappedSlice<T> {}
+unsafe impl<T: Send> Send for MappedSlice<T> {}
+
+#[cfg(feature = "builder")]
+#[bon::bon]
+impl<T> MappedSlice<T> {
+    #[builder]
+    pub fn new(
+        name: String,
+        #[builder(default)] create: bool,
+        /// Number of elements, which the creator must provide, or fail with
+        /// `Error::MissingLength`. Attachers read it from the header, and fail with `Error::SizeMismatch` if it
+        /// differs from `len` when provided.
+        len: Option<usize>,
+    ) -> crate::Result<Self> {
+        let len = match create {
+            true => len.ok_or(crate::Error::MissingLength)?,
+            false => {
+                let stored = Self::attach_len(&name)?;
+                match len {
+                    Some(expected) if expected != stored => {
+                        return Err(crate::Error::SizeMismatch {
+                            expected: Self::size(expected)?,
+                            actual: Self::size(stored)?,
+                        });
+                    }
+                    Some(_) | None => stored,
+                }
+            }
+        };
+
+        let size = Self::size(len)?;
+        let inner = Raw::builder()
+            .name(name)
+            .size(size)
+            .create(create)
+            .attach(!create)
+            .build()?;
+
+        let header = unsafe { inner.address().cast::<Header>().as_ref() };
+        match inner.file.is_create() {
+            true => {
+                header.set_len(len as u64);
+                header.publish(None);
+            }
+            false => inner.file.validate_size(size)?,
+        }
+
+        Ok(Self {
+            inner,
+            len,
+            r#type: PhantomData,
+        })
+    }
+}
+
+impl<T> MappedSlice<T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.data(), self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.data(), self.len) }
+    }
+
+    pub fn unlink(&mut self) -> crate::Result<()> {
+        self.inner.unlink()
+    }
+
+    #[cfg(feature = "builder")]
+    fn size(len: usize) -> crate::Result<usize> {
+        len.checked_mul(size_of::<T>())
+            .and_then(|size| size.checked_add(Page::SIZE))
+            .ok_or(crate::Error::LengthOverflow { len })
+    }
+
+    // Map just the header page to read the length published by the creator.
+    #[cfg(feature = "builder")]
+    fn attach_len(name: &str) -> crate::Result<usize> {
+        let raw = Raw::builder()
+            .name(name.to_owned())
+            .size(Page::SIZE)
+            .attach(true)
+            .build()?;
+
+        let header = unsafe { raw.address().cast::<Header>().as_ref() };
+        header.validate(None)?;
+        Ok(header.len() as usize)
+    }
+
+    fn data(&self) -> *mut T {
+        unsafe { self.inner.address().add(1).cast::<T>().as_ptr() }
+    }
+}
+
+impl<T: 'static> crate::Teardown for MappedSlice<T> {
+    fn unlink(&mut self) -> crate::Result<()> {
+        MappedSlice::unlink(self)
+    }
+}
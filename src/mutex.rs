@@ -0,0 +1,178 @@
+use core::mem::MaybeUninit;
+
+#[cfg(feature = "builder")]
+use crate::Numa;
+#[cfg(feature = "builder")]
+use crate::Populate;
+use crate::Shm;
+use crate::try_pthread;
+
+/// Process-shared `pthread` mutex, e.g. to protect a data structure
+/// mapped in another segment.
+pub struct Mutex {
+    inner: Shm<libc::pthread_mutex_t>,
+}
+
+/// Releases the [`Mutex`] when dropped.
+pub struct MutexGuard<'mutex> {
+    mutex: &'mutex Mutex,
+}
+
+/// Outcome of acquiring a [`Mutex`], which only differs from `Acquired`
+/// for robust mutexes.
+pub enum LockResult<'mutex> {
+    Acquired(MutexGuard<'mutex>),
+    /// The previous owner died while holding the mutex, which the calling thread
+    /// now holds. Repair any shared state, then call [`Mutex::make_consistent`];
+    /// otherwise, dropping the guard leaves the mutex not recoverable.
+    OwnerDead(MutexGuard<'mutex>),
+    /// A previous owner died and the mutex was released without being made
+    /// consistent, so it can no longer be acquired. See [`Mutex::force_reinit`].
+    NotRecoverable,
+}
+
+unsafe impl Sync for Mutex {}
+unsafe impl Send for Mutex {}
+
+#[cfg(feature = "builder")]
+#[bon::bon]
+impl Mutex {
+    #[builder]
+    pub fn new(
+        name: String,
+        #[builder(default)] create: bool,
+        /// Use `PTHREAD_MUTEX_ROBUST`, so that locking a mutex whose owner died
+        /// returns [`LockResult::OwnerDead`] instead of blocking forever.
+        #[builder(default)]
+        robust: bool,
+        numa: Option<Numa>,
+        populate: Option<Populate>,
+    ) -> crate::Result<Self> {
+        let inner = Shm::<libc::pthread_mutex_t>::builder()
+            .name(name)
+            .create(create)
+            .maybe_numa(numa)
+            .maybe_populate(populate)
+            .build()?;
+
+        if create {
+            Self::init(&inner, robust)?;
+        }
+
+        Ok(Self { inner })
+    }
+}
+
+impl Mutex {
+    /// Reopen the existing mutex `name` and initialize its `pthread` object from
+    /// scratch. Intended for crash recovery tooling, when a process died while
+    /// holding a non-robust mutex.
+    /// Fails with `ENOENT` if the mutex doesn't exist.
+    ///
+    /// Only safe when no process is using the mutex. See [`crate::Barrier::force_reinit`].
+    pub fn force_reinit(name: String, robust: bool) -> crate::Result<Self> {
+        let inner = Shm::<libc::pthread_mutex_t>::with_options(
+            name,
+            crate::raw::Options {
+                attach: true,
+                ..crate::raw::Options::default()
+            },
+        )?;
+        Self::init(&inner, robust)?;
+        Ok(Self { inner })
+    }
+
+    /// Block until the mutex is acquired. See [`LockResult`] for robust mutexes.
+    pub fn lock(&self) -> crate::Result<LockResult<'_>> {
+        self.acquire(unsafe { try_pthread!(libc::pthread_mutex_lock(self.mutex())) })
+            .map(Option::unwrap)
+    }
+
+    /// Acquire the mutex if it is not held, returning `None` otherwise.
+    /// See [`Mutex::lock`].
+    pub fn try_lock(&self) -> crate::Result<Option<LockResult<'_>>> {
+        self.acquire(unsafe { try_pthread!(libc::pthread_mutex_trylock(self.mutex())) })
+    }
+
+    /// Mark a robust mutex as consistent after [`LockResult::OwnerDead`], while
+    /// the calling thread still holds it, so that later owners acquire it normally.
+    pub fn make_consistent(&self) -> crate::Result<()> {
+        unsafe { try_pthread!(libc::pthread_mutex_consistent(self.mutex())) }
+    }
+
+    pub fn unlink(&mut self) -> crate::Result<()> {
+        unsafe { try_pthread!(libc::pthread_mutex_destroy(self.mutex()))? }
+        self.inner.unlink()
+    }
+
+    fn init(inner: &Shm<libc::pthread_mutex_t>, robust: bool) -> crate::Result<()> {
+        let mut attr = unsafe {
+            let mut attr = MaybeUninit::<libc::pthread_mutexattr_t>::zeroed();
+            try_pthread!(libc::pthread_mutexattr_init(attr.as_mut_ptr()))?;
+            try_pthread!(libc::pthread_mutexattr_setpshared(
+                attr.as_mut_ptr(),
+                libc::PTHREAD_PROCESS_SHARED
+            ))?;
+            if robust {
+                try_pthread!(libc::pthread_mutexattr_setrobust(
+                    attr.as_mut_ptr(),
+                    libc::PTHREAD_MUTEX_ROBUST
+                ))?;
+            }
+            attr.assume_init()
+        };
+
+        unsafe {
+            try_pthread!(libc::pthread_mutex_init(inner.address().as_ptr(), &attr))?;
+        }
+
+        unsafe {
+            assert_eq!(libc::pthread_mutexattr_destroy(&mut attr), 0);
+        }
+
+        Ok(())
+    }
+
+    fn acquire(&self, result: crate::Result<()>) -> crate::Result<Option<LockResult<'_>>> {
+        match result {
+            Ok(()) => Ok(Some(LockResult::Acquired(MutexGuard { mutex: self }))),
+            Err(crate::Error::Libc { name, source }) => match source.raw_os_error() {
+                Some(libc::EBUSY) => Ok(None),
+                Some(libc::EOWNERDEAD) => {
+                    Ok(Some(LockResult::OwnerDead(MutexGuard { mutex: self })))
+                }
+                Some(libc::ENOTRECOVERABLE) => Ok(Some(LockResult::NotRecoverable)),
+                _ => Err(crate::Error::Libc { name, source }),
+            },
+            Err(error) => Err(error),
+        }
+    }
+
+    pub(crate) fn mutex(&self) -> *mut libc::pthread_mutex_t {
+        self.inner.address().as_ptr()
+    }
+}
+
+impl MutexGuard<'_> {
+    pub(crate) fn mutex(&self) -> *mut libc::pthread_mutex_t {
+        self.mutex.mutex()
+    }
+}
+
+impl Drop for MutexGuard<'_> {
+    fn drop(&mut self) {
+        if let Err(error) = unsafe { try_pthread!(libc::pthread_mutex_unlock(self.mutex())) } {
+            log::error!("Failed to unlock mutex: {}", error);
+        }
+    }
+}
+
+impl crate::Teardown for Mutex {
+    fn destroy(&mut self) -> crate::Result<()> {
+        unsafe { try_pthread!(libc::pthread_mutex_destroy(self.mutex())) }
+    }
+
+    fn unlink(&mut self) -> crate::Result<()> {
+        self.inner.unlink()
+    }
+}
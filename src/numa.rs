@@ -1,19 +1,187 @@
 use core::ffi;
+use core::ptr;
+use std::fs;
+use std::io;
 
+use crate::Page;
 use crate::try_libc;
 
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(tag = "policy", rename_all = "snake_case"))]
 pub enum Numa {
-    Bind { node: usize },
-    Interleave { nodes: Vec<usize> },
+    Bind {
+        node: usize,
+    },
+    Interleave {
+        nodes: Vec<usize>,
+        /// Interleave in stripes of this many bytes instead of single pages,
+        /// e.g. 2MB stripes for coarse-grained tiering. Must be a multiple of
+        /// the page size. Only applies to [`Numa::mbind`], which binds each
+        /// stripe to the next node in `nodes`, round-robin.
+        #[cfg_attr(feature = "serde", serde(default))]
+        stripe: Option<usize>,
+    },
+    /// Prefer `node`, but fall back to other nodes under memory pressure
+    /// instead of failing with `ENOMEM` like `Bind`.
+    Preferred {
+        node: usize,
+    },
+    /// Prefer the node of the CPU that triggers the allocation.
+    Local,
+}
+
+/// Online NUMA nodes and their memory, from `/sys/devices/system/node/`.
+#[derive(Clone, Debug)]
+pub struct Topology {
+    pub nodes: Vec<usize>,
+    // Total and free bytes of each node in `nodes`.
+    memory: Vec<(usize, usize)>,
+}
+
+impl Topology {
+    /// Total bytes of memory on `node`, or `None` if it is not online.
+    pub fn total(&self, node: usize) -> Option<usize> {
+        self.memory(node).map(|(total, _)| total)
+    }
+
+    /// Free bytes of memory on `node`, or `None` if it is not online.
+    pub fn free(&self, node: usize) -> Option<usize> {
+        self.memory(node).map(|(_, free)| free)
+    }
+
+    /// Online node with the most free memory.
+    pub fn emptiest(&self) -> Option<usize> {
+        self.nodes
+            .iter()
+            .zip(&self.memory)
+            .max_by_key(|(_, (_, free))| *free)
+            .map(|(node, _)| *node)
+    }
+
+    fn memory(&self, node: usize) -> Option<(usize, usize)> {
+        let index = self.nodes.iter().position(|online| *online == node)?;
+        Some(self.memory[index])
+    }
+}
+
+/// Read the online NUMA nodes and their memory, e.g. to validate the nodes of
+/// a [`Numa::Interleave`] policy before passing them to `mbind`.
+pub fn topology() -> crate::Result<Topology> {
+    const ROOT: &str = "/sys/devices/system/node";
+
+    let read = |path: String| {
+        fs::read_to_string(path).map_err(|source| crate::Error::Libc {
+            name: "read",
+            source,
+        })
+    };
+
+    let invalid = |path: &str| crate::Error::Libc {
+        name: "read",
+        source: io::Error::new(io::ErrorKind::InvalidData, format!("malformed {path}")),
+    };
+
+    // Ranges like `0-3,5`.
+    let online = format!("{ROOT}/online");
+    let mut nodes = Vec::new();
+    for range in read(online.clone())?.trim().split(',') {
+        let (start, end) = range.split_once('-').unwrap_or((range, range));
+        let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>()) else {
+            return Err(invalid(&online));
+        };
+        nodes.extend(start..=end);
+    }
+
+    // Lines like `Node 0 MemTotal:       32768 kB`.
+    let memory = nodes
+        .iter()
+        .map(|node| {
+            let path = format!("{ROOT}/node{node}/meminfo");
+            let meminfo = read(path.clone())?;
+            let field = |name: &str| {
+                meminfo
+                    .lines()
+                    .filter_map(|line| line.split_once(':'))
+                    .find(|(key, _)| key.ends_with(name))
+                    .and_then(|(_, value)| value.trim().strip_suffix(" kB"))
+                    .and_then(|kb| kb.trim().parse::<usize>().ok())
+                    .map(|kb| kb * 1024)
+                    .ok_or_else(|| invalid(&path))
+            };
+            Ok((field("MemTotal")?, field("MemFree")?))
+        })
+        .collect::<crate::Result<_>>()?;
+
+    Ok(Topology { nodes, memory })
+}
+
+/// Result of [`Numa::migrate`].
+#[derive(Debug)]
+pub struct Migration {
+    /// Node of each page in the range after migrating, or why it couldn't be
+    /// located or moved, e.g. `ENOENT` for pages not yet faulted in.
+    pub status: Vec<Result<usize, io::Error>>,
+
+    /// Number of pages actually moved to their target node.
+    pub moved: usize,
+}
+
+/// Flags passed to `mbind`. Defaults to none, which only applies the policy
+/// to pages allocated after the call.
+#[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct MbindFlags {
+    /// Fail with `EIO` if existing pages in the range don't follow the policy.
+    ///
+    /// Note: `MPOL_MF_STRICT` sometimes raises `EIO` when called concurrently for
+    /// the same address range, so only set this when calls are serialized.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub strict: bool,
+
+    /// Move existing pages used exclusively by this process to conform to the policy.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub r#move: bool,
+
+    /// Move all existing pages in the range, even if shared with other processes.
+    /// Requires `CAP_SYS_NICE`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub move_all: bool,
+}
+
+impl MbindFlags {
+    // https://github.com/torvalds/linux/blob/0c559323bbaabee7346c12e74b497e283aaafef5/include/uapi/linux/mempolicy.h#L48-L50
+    const MPOL_MF_STRICT: libc::c_uint = 1 << 0;
+    const MPOL_MF_MOVE: libc::c_uint = 1 << 1;
+    const MPOL_MF_MOVE_ALL: libc::c_uint = 1 << 2;
+
+    fn bits(&self) -> libc::c_uint {
+        let mut bits = 0;
+        if self.strict {
+            bits |= Self::MPOL_MF_STRICT;
+        }
+        if self.r#move {
+            bits |= Self::MPOL_MF_MOVE;
+        }
+        if self.move_all {
+            bits |= Self::MPOL_MF_MOVE_ALL;
+        }
+        bits
+    }
 }
 
 impl Numa {
+    /// Number of nodes supported by the node mask passed to the kernel.
+    pub const MAX_NODES: usize = libc::c_ulong::BITS as usize;
+
     // SAFETY: `mbind` will not dereference invalid address.
     #[expect(clippy::not_unsafe_ptr_arg_deref)]
-    pub fn mbind(&self, address: *mut ffi::c_void, size: usize) -> crate::Result<()> {
+    pub fn mbind(
+        &self,
+        address: *mut ffi::c_void,
+        size: usize,
+        flags: MbindFlags,
+    ) -> crate::Result<()> {
         // Call syscall to avoid external C dependency on `libnuma`.
         //
         // https://github.com/numactl/numactl/blob/6c14bd59d438ebb5ef828e393e8563ba18f59cb2/syscall.c#L230-L235
@@ -28,7 +196,18 @@ impl Numa {
             unsafe { libc::syscall(libc::SYS_mbind, address, size, mode, mask, maxnode, flags) }
         }
 
-        let (mode, mask) = self.to_mode_mask();
+        // Let the kernel reject an empty node list, as for unstriped interleaving.
+        match self {
+            Numa::Interleave {
+                nodes,
+                stripe: Some(stripe),
+            } if !nodes.is_empty() => {
+                return Self::mbind_striped(address, size, flags, nodes, *stripe);
+            }
+            _ => (),
+        }
+
+        let (mode, mask) = self.to_mode_mask()?;
 
         unsafe {
             try_libc!(mbind_syscall(
@@ -36,17 +215,39 @@ impl Numa {
                 size as u64,
                 mode,
                 &mask,
-                64,
-                // MPOL_MF_STRICT sometimes raises EIO when called concurrently for the same
-                // address range, so disable for now.
-                // https://github.com/torvalds/linux/blob/0c559323bbaabee7346c12e74b497e283aaafef5/include/uapi/linux/mempolicy.h#L48
-                0,
+                Self::MAX_NODES as libc::c_ulong,
+                flags.bits(),
             ))?;
         }
 
         Ok(())
     }
 
+    // Kernel interleaving is per page, so bind each stripe separately.
+    fn mbind_striped(
+        address: *mut ffi::c_void,
+        size: usize,
+        flags: MbindFlags,
+        nodes: &[usize],
+        stripe: usize,
+    ) -> crate::Result<()> {
+        if stripe == 0 || stripe % Page::SIZE != 0 {
+            return Err(crate::Error::NumaStripe { stripe });
+        }
+
+        for (offset, node) in (0..size).step_by(stripe).zip(nodes.iter().cycle()) {
+            Numa::Bind { node: *node }.mbind(
+                address.wrapping_byte_add(offset),
+                stripe.min(size - offset),
+                flags,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Interleaves at page granularity regardless of [`Numa::Interleave`]'s
+    /// `stripe`, since the thread policy has no address range to split.
     pub fn set_mempolicy(&self) -> crate::Result<()> {
         // Call syscall to avoid external C dependency on `libnuma`.
         //
@@ -59,24 +260,243 @@ impl Numa {
             unsafe { libc::syscall(libc::SYS_set_mempolicy, mode, mask, maxnode) }
         }
 
-        let (mode, mask) = self.to_mode_mask();
+        let (mode, mask) = self.to_mode_mask()?;
 
         unsafe {
-            try_libc!(set_mempolicy_syscall(mode, &mask, 64,))?;
+            try_libc!(set_mempolicy_syscall(
+                mode,
+                &mask,
+                Self::MAX_NODES as libc::c_ulong
+            ))?;
         }
 
         Ok(())
     }
 
-    fn to_mode_mask(&self) -> (libc::c_int, libc::c_ulong) {
+    /// Policy of the calling thread, or of the page containing `address` if provided,
+    /// along with its `MPOL_F_*` mode flags. Returns `None` for `MPOL_DEFAULT`.
+    // SAFETY: `get_mempolicy` will not dereference invalid address.
+    #[expect(clippy::not_unsafe_ptr_arg_deref)]
+    pub fn get_mempolicy(
+        address: Option<*mut ffi::c_void>,
+    ) -> crate::Result<Option<(Self, libc::c_int)>> {
+        // Call syscall to avoid external C dependency on `libnuma`.
+        //
+        // https://man7.org/linux/man-pages/man2/get_mempolicy.2.html
+        unsafe fn get_mempolicy_syscall(
+            mode: *mut libc::c_int,
+            mask: *mut libc::c_ulong,
+            maxnode: libc::c_ulong,
+            address: *mut ffi::c_void,
+            flags: libc::c_ulong,
+        ) -> i64 {
+            unsafe { libc::syscall(libc::SYS_get_mempolicy, mode, mask, maxnode, address, flags) }
+        }
+
+        // https://github.com/torvalds/linux/blob/0c559323bbaabee7346c12e74b497e283aaafef5/include/uapi/linux/mempolicy.h#L56-L58
+        const MPOL_F_ADDR: libc::c_ulong = 1 << 1;
+
+        let mut mode = 0;
+        let mut mask = 0;
+
+        unsafe {
+            try_libc!(get_mempolicy_syscall(
+                &mut mode,
+                &mut mask,
+                Self::MAX_NODES as libc::c_ulong,
+                address.unwrap_or(ptr::null_mut()),
+                match address {
+                    Some(_) => MPOL_F_ADDR,
+                    None => 0,
+                },
+            ))?;
+        }
+
+        Ok(Self::from_mode_mask(mode, mask))
+    }
+
+    /// Node that each page overlapping `size` bytes at `address` currently
+    /// resides on, or `None` for pages not yet faulted in. Useful to verify
+    /// that [`Numa::mbind`] placed pages as requested.
+    // SAFETY: `move_pages` will not dereference invalid address.
+    #[expect(clippy::not_unsafe_ptr_arg_deref)]
+    pub fn node_of(address: *const ffi::c_void, size: usize) -> crate::Result<Vec<Option<usize>>> {
+        let pages = Self::pages(address, size);
+        move_pages(&pages, None)?
+            .into_iter()
+            .map(|status| match status {
+                0.. => Ok(Some(status as usize)),
+                _ if -status == libc::ENOENT => Ok(None),
+                _ => Err(crate::Error::Libc {
+                    name: "move_pages",
+                    source: io::Error::from_raw_os_error(-status),
+                }),
+            })
+            .collect()
+    }
+
+    /// Move already populated pages overlapping `size` bytes at `address` that
+    /// reside on `from[i]` to `to[i]`, unlike [`Numa::mbind`], which by default
+    /// only places pages allocated afterwards. Pages used by other processes
+    /// are left in place.
+    // SAFETY: `move_pages` will not dereference invalid address.
+    #[expect(clippy::not_unsafe_ptr_arg_deref)]
+    pub fn migrate(
+        address: *const ffi::c_void,
+        size: usize,
+        from: &[usize],
+        to: &[usize],
+    ) -> crate::Result<Migration> {
+        assert_eq!(
+            from.len(),
+            to.len(),
+            "Expected one target node per source node"
+        );
+
+        let pages = Self::pages(address, size);
+        let mut status = move_pages(&pages, None)?;
+
+        let (moving, targets): (Vec<_>, Vec<_>) = status
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &node)| {
+                let source = from.iter().position(|&from| from as libc::c_int == node)?;
+                Some((index, to[source] as libc::c_int))
+            })
+            .unzip();
+
+        let moved = moving.iter().map(|&index| pages[index]).collect::<Vec<_>>();
+        let after = move_pages(&moved, Some(&targets))?;
+
+        for (&index, &node) in moving.iter().zip(&after) {
+            status[index] = node;
+        }
+
+        Ok(Migration {
+            moved: after
+                .iter()
+                .zip(&targets)
+                .filter(|(after, target)| after == target)
+                .count(),
+            status: status
+                .into_iter()
+                .map(|status| match status {
+                    0.. => Ok(status as usize),
+                    _ => Err(io::Error::from_raw_os_error(-status)),
+                })
+                .collect(),
+        })
+    }
+
+    // Start of each page overlapping `size` bytes at `address`.
+    fn pages(address: *const ffi::c_void, size: usize) -> Vec<*const ffi::c_void> {
+        let start = address.addr() / Page::SIZE * Page::SIZE;
+        (start..address.addr() + size)
+            .step_by(Page::SIZE)
+            .map(|page| address.with_addr(page))
+            .collect()
+    }
+
+    fn to_mode_mask(&self) -> crate::Result<(libc::c_int, libc::c_ulong)> {
         let (mode, mask) = match self {
-            Numa::Bind { node } => (libc::MPOL_BIND, 1u64 << node),
-            Numa::Interleave { nodes } => (
+            Numa::Bind { node } => (libc::MPOL_BIND, Self::bit(*node)?),
+            Numa::Interleave { nodes, .. } => (
                 libc::MPOL_INTERLEAVE,
-                nodes.iter().map(|node| 1u64 << node).fold(0, |l, r| l | r),
+                nodes
+                    .iter()
+                    .map(|node| Self::bit(*node))
+                    .try_fold(0, |l, r| r.map(|r| l | r))?,
             ),
+            Numa::Preferred { node } => (libc::MPOL_PREFERRED, Self::bit(*node)?),
+            // Requires an empty mask, which mode flags are not allowed to modify.
+            Numa::Local => return Ok((libc::MPOL_LOCAL, 0)),
+        };
+
+        Ok((mode | libc::MPOL_F_STATIC_NODES, mask))
+    }
+
+    // Nodes beyond the width of the single-word mask would otherwise overflow the shift.
+    fn bit(node: usize) -> crate::Result<libc::c_ulong> {
+        match node < Self::MAX_NODES {
+            true => Ok(1 << node),
+            false => Err(crate::Error::NumaNode { node }),
+        }
+    }
+
+    // Inverse of `to_mode_mask`, also returning the mode flags.
+    fn from_mode_mask(mode: libc::c_int, mask: libc::c_ulong) -> Option<(Self, libc::c_int)> {
+        let flags = libc::MPOL_F_STATIC_NODES | libc::MPOL_F_RELATIVE_NODES;
+        let nodes = (0..Self::MAX_NODES).filter(|node| mask & (1 << node) != 0);
+
+        let numa = match mode & !flags {
+            libc::MPOL_BIND => Numa::Bind {
+                node: mask.trailing_zeros() as usize,
+            },
+            libc::MPOL_INTERLEAVE => Numa::Interleave {
+                nodes: nodes.collect(),
+                stripe: None,
+            },
+            // An empty preferred mask is the legacy encoding of local allocation.
+            libc::MPOL_PREFERRED if mask == 0 => Numa::Local,
+            libc::MPOL_PREFERRED => Numa::Preferred {
+                node: mask.trailing_zeros() as usize,
+            },
+            libc::MPOL_LOCAL => Numa::Local,
+            // `MPOL_DEFAULT`, or a policy not modeled by `Numa`.
+            _ => return None,
         };
 
-        (mode | libc::MPOL_F_STATIC_NODES, mask)
+        Some((numa, mode & flags))
+    }
+}
+
+// Call syscall to avoid external C dependency on `libnuma`.
+//
+// https://man7.org/linux/man-pages/man2/move_pages.2.html
+unsafe fn move_pages_syscall(
+    pid: libc::pid_t,
+    count: libc::c_ulong,
+    pages: *const *const ffi::c_void,
+    nodes: *const libc::c_int,
+    status: *mut libc::c_int,
+    flags: libc::c_int,
+) -> i64 {
+    unsafe {
+        libc::syscall(
+            libc::SYS_move_pages,
+            pid,
+            count,
+            pages,
+            nodes,
+            status,
+            flags,
+        )
     }
 }
+
+// Node of each page, after moving the pages to `nodes` if provided.
+fn move_pages(
+    pages: &[*const ffi::c_void],
+    nodes: Option<&[libc::c_int]>,
+) -> crate::Result<Vec<libc::c_int>> {
+    let mut status = vec![0 as libc::c_int; pages.len()];
+    if pages.is_empty() {
+        return Ok(status);
+    }
+
+    unsafe {
+        try_libc!(move_pages_syscall(
+            0,
+            pages.len() as libc::c_ulong,
+            pages.as_ptr(),
+            nodes.map(<[_]>::as_ptr).unwrap_or(ptr::null()),
+            status.as_mut_ptr(),
+            match nodes {
+                Some(_) => MbindFlags::MPOL_MF_MOVE as libc::c_int,
+                None => 0,
+            },
+        ))?;
+    }
+
+    Ok(status)
+}
@@ -0,0 +1,137 @@
+use core::ffi;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::Page;
+use crate::backend::madvise;
+
+static THREADS: AtomicUsize = AtomicUsize::new(1);
+static POOL: Mutex<Option<Pool>> = Mutex::new(None);
+
+/// Configure the number of threads used to fault in `Populate::Physical` mappings.
+///
+/// The pool is shared by all mappings and started lazily on first use, so this
+/// must be called before mapping to take effect. Returns `false` without
+/// changing the configuration if the pool was already started. Defaults to a
+/// single thread, which populates inline on the calling thread without
+/// starting a pool.
+pub fn configure_prefault_pool(threads: usize) -> bool {
+    let pool = POOL.lock().unwrap_or_else(|poison| poison.into_inner());
+    if pool.is_some() {
+        return false;
+    }
+
+    THREADS.store(threads, Ordering::Relaxed);
+    true
+}
+
+/// Stop the prefault pool, if started, and wait for its threads to exit after
+/// finishing any queued work.
+///
+/// The next `Populate::Physical` mapping starts a new pool, so this can be
+/// followed by [`configure_prefault_pool`] to resize it.
+pub fn shutdown_prefault_pool() {
+    let pool = POOL
+        .lock()
+        .unwrap_or_else(|poison| poison.into_inner())
+        .take();
+
+    if let Some(pool) = pool {
+        pool.shutdown();
+    }
+}
+
+pub(crate) fn populate(address: *mut ffi::c_void, size: usize) -> crate::Result<()> {
+    let threads = THREADS.load(Ordering::Relaxed);
+    if threads <= 1 {
+        return madvise(address, size, libc::MADV_POPULATE_WRITE);
+    }
+
+    // Clone the sender so the lock isn't held while waiting for results.
+    let sender = POOL
+        .lock()
+        .unwrap_or_else(|poison| poison.into_inner())
+        .get_or_insert_with(|| Pool::new(threads))
+        .sender
+        .clone();
+    let chunk = size.div_ceil(threads).next_multiple_of(Page::SIZE);
+    let (done, results) = mpsc::channel();
+
+    for offset in (0..size).step_by(chunk) {
+        sender
+            .send(Job {
+                address: address as usize + offset,
+                size: chunk.min(size - offset),
+                done: done.clone(),
+            })
+            .expect("Prefault pool exited");
+    }
+
+    // Iteration ends once every job has dropped its sender. Wait for all jobs
+    // even on error so no worker touches the mapping after we return.
+    drop(done);
+    results.iter().fold(Ok(()), |all, result| all.and(result))
+}
+
+struct Pool {
+    sender: mpsc::Sender<Job>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+struct Job {
+    address: usize,
+    size: usize,
+    done: mpsc::Sender<crate::Result<()>>,
+}
+
+impl Pool {
+    // Workers block on the shared queue and hold no resources between jobs,
+    // so they don't need to be joined before the process exits, but can be
+    // with `shutdown`.
+    fn new(threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..threads).map(|index| {
+            let receiver = Arc::clone(&receiver);
+            thread::Builder::new()
+                .name(format!("shm-prefault-{index}"))
+                .spawn(move || {
+                    loop {
+                        let job = receiver.lock().unwrap().recv();
+                        let Ok(job) = job else {
+                            return;
+                        };
+
+                        let result = madvise(
+                            job.address as *mut ffi::c_void,
+                            job.size,
+                            libc::MADV_POPULATE_WRITE,
+                        );
+                        let _ = job.done.send(result);
+                    }
+                })
+                .expect("Failed to spawn prefault thread")
+        });
+
+        Self {
+            sender,
+            workers: workers.collect(),
+        }
+    }
+
+    // Workers exit once the queue is drained and every sender is dropped,
+    // including clones held by in-flight calls to `populate`.
+    fn shutdown(self) {
+        drop(self.sender);
+        for worker in self.workers {
+            if worker.join().is_err() {
+                log::error!("Prefault thread panicked");
+            }
+        }
+    }
+}
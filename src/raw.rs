@@ -1,31 +1,158 @@
+use core::mem;
+use core::mem::MaybeUninit;
 use core::num::NonZeroUsize;
+use core::ops::Range;
+use core::ptr;
 use core::ptr::NonNull;
 use std::ffi;
+use std::io::Write as _;
+use std::os::fd::AsRawFd as _;
+use std::os::fd::BorrowedFd;
+use std::os::fd::OwnedFd;
+use std::slice;
 
-use bon::bon;
-
+use crate::Advice;
+use crate::Backend;
+use crate::HugePage;
+use crate::MbindFlags;
 use crate::Numa;
 use crate::Page;
 use crate::Populate;
-use crate::backend::Interface as _;
+use crate::Protection;
+use crate::Reservation;
+use crate::backend::File;
+use crate::backend::MapOptions;
+use crate::try_libc;
+
+/// Device and inode of the object backing a segment. Equal identities refer to
+/// the same object, so comparing them detects a segment that was unlinked and
+/// recreated under the same name.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SegmentIdentity {
+    pub dev: libc::dev_t,
+    pub ino: libc::ino64_t,
+}
 
 pub struct Raw {
-    pub(crate) name: String,
+    // `None` for mappings of imported file descriptors.
+    pub(crate) name: Option<ffi::CString>,
     pub(crate) size: NonZeroUsize,
     pub(crate) address: NonNull<Page>,
+    pub(crate) file: File,
+    pub(crate) backend: Backend,
+    // Only honored by the process that created the segment.
+    pub(crate) unlink_on_drop: bool,
 }
 
-#[bon]
+#[cfg(feature = "builder")]
+#[bon::bon]
 impl Raw {
     #[builder]
     pub fn new(
         name: String,
         size: usize,
         #[builder(default)] create: bool,
+        /// Only open an existing segment, failing with `ENOENT` instead of creating
+        /// one. Fails with `EINVAL` if `create` is also set.
+        #[builder(default)]
+        attach: bool,
         numa: Option<Numa>,
+        /// Flags for applying `numa`. See [`MbindFlags`].
+        #[builder(default)]
+        mbind_flags: MbindFlags,
         populate: Option<Populate>,
+        #[builder(default)] unlink_on_drop: bool,
+        /// Defaults to [`Backend::Shm`].
+        #[builder(into)]
+        backend: Option<Backend>,
+        /// Permission bits for a newly created shm object. Defaults to `0o666`,
+        /// masked by the process umask. Ignored when attaching, or for other backends.
+        mode: Option<libc::mode_t>,
+        /// Defaults to the access the segment was opened with.
+        protection: Option<Protection>,
+        /// Rounds `size` up to a multiple of the huge page size.
+        #[builder(default)]
+        huge_page: HugePage,
+        /// Map at exactly this address, failing with `EEXIST` if any existing
+        /// mapping overlaps the range instead of replacing it.
+        address: Option<NonNull<Page>>,
+        /// Keep the file descriptor open across `exec`, e.g. to pass the segment
+        /// to a child process. By default, it is closed on `exec`.
+        #[builder(default)]
+        inheritable: bool,
     ) -> crate::Result<Self> {
-        let backend = crate::Backend::Shm(crate::backend::Shm);
+        Self::with_options(
+            name,
+            size,
+            Options {
+                create,
+                attach,
+                numa,
+                mbind_flags,
+                populate,
+                unlink_on_drop,
+                backend,
+                mode,
+                protection,
+                huge_page,
+                address,
+                inheritable,
+            },
+        )
+    }
+}
+
+/// Options for [`Raw::with_options`]. See [`Raw::new`] for each option.
+#[derive(Default)]
+pub(crate) struct Options {
+    pub(crate) create: bool,
+    pub(crate) attach: bool,
+    pub(crate) numa: Option<Numa>,
+    pub(crate) mbind_flags: MbindFlags,
+    pub(crate) populate: Option<Populate>,
+    pub(crate) unlink_on_drop: bool,
+    pub(crate) backend: Option<Backend>,
+    pub(crate) mode: Option<libc::mode_t>,
+    pub(crate) protection: Option<Protection>,
+    pub(crate) huge_page: HugePage,
+    pub(crate) address: Option<NonNull<Page>>,
+    pub(crate) inheritable: bool,
+}
+
+impl Raw {
+    pub(crate) fn with_options(name: String, size: usize, options: Options) -> crate::Result<Self> {
+        let Options {
+            create,
+            attach,
+            numa,
+            mbind_flags,
+            populate,
+            unlink_on_drop,
+            backend,
+            mode,
+            protection,
+            huge_page,
+            address,
+            inheritable,
+        } = options;
+
+        let backend = match (backend, mode) {
+            (None | Some(Backend::Shm(_)), Some(mode)) => {
+                Backend::Shm(crate::backend::Shm::with_mode(mode))
+            }
+            (Some(backend), _) => backend,
+            (None, None) => Backend::Shm(crate::backend::Shm::default()),
+        };
+
+        if create && attach {
+            return Err(crate::Error::Libc {
+                name: "open",
+                source: std::io::Error::from_raw_os_error(libc::EINVAL),
+            });
+        }
+
+        // Reject interior null bytes before touching the backend.
+        let id = ffi::CString::new(name.as_str()).map_err(|_| crate::Error::ShmName)?;
 
         if create {
             match backend.unlink(&name) {
@@ -35,24 +162,136 @@ impl Raw {
             }
         }
 
-        let size = NonZeroUsize::new(size).unwrap();
-        let file = backend.open(&name, size)?;
+        let size = NonZeroUsize::new(size.next_multiple_of(huge_page.size())).unwrap();
+        let mut file = match attach {
+            true => backend.attach(&name, size)?,
+            false => backend.open(&name, size)?,
+        };
+        if file.is_create() {
+            crate::cleanup::track(&backend, &name);
+        }
+        if let Some(protection) = protection {
+            file.set_protection(protection);
+        }
+
+        if inheritable {
+            file.set_inheritable()?;
+        }
+
         let address = unsafe {
-            file.map()
-                .maybe_numa(numa)
-                .maybe_populate(populate)
-                .call()?
+            file.map_with(MapOptions {
+                address,
+                fixed_no_replace: true,
+                numa,
+                mbind_flags,
+                populate,
+                huge_page,
+                ..MapOptions::default()
+            })?
         };
 
         Ok(Self {
+            name: Some(id),
+            // May be larger than requested when attaching to an existing segment.
+            size: file.size().max(size),
+            address,
+            file,
+            backend,
+            unlink_on_drop,
+        })
+    }
+
+    /// Shorthand for `Raw::builder().name(name).size(size).create(create).build()`.
+    pub fn named(name: String, size: usize, create: bool) -> crate::Result<Self> {
+        Self::with_options(
             name,
             size,
+            Options {
+                create,
+                ..Options::default()
+            },
+        )
+    }
+
+    /// Map an externally created file descriptor, e.g. a dma-buf exported by a GPU driver.
+    ///
+    /// Fails with `Error::SizeMismatch` if the file is smaller than `size`.
+    pub fn from_owned_fd(fd: OwnedFd, size: usize, create: bool) -> crate::Result<Self> {
+        let size = NonZeroUsize::new(size).unwrap();
+        let file = File::new(
+            Some(fd),
+            NonZeroUsize::new(size.get().next_multiple_of(Page::SIZE)).unwrap(),
+            0,
+            create,
+        );
+
+        file.validate_size(size.get())?;
+        let address = unsafe { file.map_with(MapOptions::default())? };
+
+        Ok(Self {
+            name: None,
+            size,
             address,
+            file,
+            backend: Backend::Mmap(crate::backend::Mmap),
+            unlink_on_drop: false,
+        })
+    }
+
+    /// Attach to an existing shm segment of at least `size` bytes without write access.
+    /// Writes through the mapping fault with `SIGSEGV`.
+    pub fn open_readonly(name: String, size: usize) -> crate::Result<Self> {
+        let id = ffi::CString::new(name.as_str()).map_err(|_| crate::Error::ShmName)?;
+        let size = NonZeroUsize::new(size).unwrap();
+        let file = crate::backend::Shm::default().open_readonly(&name, size)?;
+        let address = unsafe { file.map_with(MapOptions::default())? };
+
+        Ok(Self {
+            name: Some(id),
+            size,
+            address,
+            file,
+            backend: Backend::Shm(crate::backend::Shm::default()),
+            unlink_on_drop: false,
+        })
+    }
+
+    /// Map a private copy-on-write view of the segment, e.g. to speculatively
+    /// modify the contents without affecting other processes. Writes through the
+    /// view are invisible to others and lost when it is unmapped, while writes by
+    /// others to pages not yet modified through the view may or may not be visible.
+    ///
+    /// Anonymous mappings have no file to map again, so their view is a copy instead.
+    pub fn private_view(&self) -> crate::Result<Self> {
+        let file = self.file.try_clone()?;
+        let address = unsafe {
+            file.map_with(MapOptions {
+                protection: Some(Protection::ReadWrite),
+                private: true,
+                ..MapOptions::default()
+            })?
+        };
+
+        if file.as_raw_fd() < 0 {
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    self.address.as_ptr().cast::<u8>(),
+                    address.as_ptr().cast::<u8>(),
+                    self.size.get(),
+                )
+            };
+        }
+
+        Ok(Self {
+            name: None,
+            size: self.size,
+            address,
+            file,
+            backend: Backend::Mmap(crate::backend::Mmap),
+            unlink_on_drop: false,
         })
     }
-}
 
-impl Raw {
     pub fn address(&self) -> NonNull<Page> {
         self.address
     }
@@ -61,13 +300,502 @@ impl Raw {
         self.size
     }
 
+    /// Whether this process created the segment or attached to an existing one.
+    pub fn role(&self) -> crate::SegmentRole {
+        match self.file.is_create() {
+            true => crate::SegmentRole::Creator,
+            false => crate::SegmentRole::Attacher,
+        }
+    }
+
+    /// File descriptor backing the mapping, e.g. to pass a [`backend::Memfd`](crate::backend::Memfd)
+    /// to another process. `None` for anonymous mappings.
+    pub fn as_fd(&self) -> Option<BorrowedFd<'_>> {
+        match self.file.as_raw_fd() {
+            -1 => None,
+            fd => Some(unsafe { BorrowedFd::borrow_raw(fd) }),
+        }
+    }
+
+    /// Grow the mapping to `size` bytes, extending the backing file, and return
+    /// the base address, which may have moved.
+    ///
+    /// Note: moving invalidates outstanding pointers into the mapping, and is likely
+    /// for anonymous mappings (e.g. [`backend::Mmap`](crate::backend::Mmap)) in
+    /// particular. Use [`Raw::grow_within`] to grow in place instead.
+    pub fn grow(&mut self, size: usize) -> crate::Result<NonNull<Page>> {
+        let (old, new) = self.resize(size)?;
+        let address = unsafe {
+            try_libc!(libc::mremap(
+                self.address.as_ptr().cast(),
+                old,
+                new,
+                libc::MREMAP_MAYMOVE,
+            ))
+        }?;
+
+        self.address = NonNull::new(address).unwrap().cast();
+        self.size = NonZeroUsize::new(size).unwrap();
+        Ok(self.address)
+    }
+
+    /// Grow the mapping to `size` bytes in place, by mapping the extension of the
+    /// backing file with `MAP_FIXED` over the `reservation` that the mapping was
+    /// placed into. Fails with `Error::ReservationOverflow` if the grown mapping
+    /// would not fit within the reservation.
+    pub fn grow_within<const SIZE: usize>(
+        &mut self,
+        size: usize,
+        reservation: &Reservation<SIZE>,
+    ) -> crate::Result<NonNull<Page>> {
+        let start = self.address.addr().get();
+        let (base, end) = (
+            reservation.start().addr().get(),
+            reservation.end().addr().get(),
+        );
+
+        if start < base || start + size.next_multiple_of(Page::SIZE) > end {
+            return Err(crate::Error::ReservationOverflow {
+                offset: start.wrapping_sub(base),
+                size,
+                capacity: SIZE,
+            });
+        }
+
+        let (old, new) = self.resize(size)?;
+        if new > old {
+            unsafe {
+                try_libc!(libc::mmap64(
+                    self.address.byte_add(old).as_ptr().cast(),
+                    new - old,
+                    self.file.protection().prot(),
+                    self.file.flags() | libc::MAP_FIXED,
+                    self.file.as_raw_fd(),
+                    self.file.offset() + old as i64,
+                ))
+            }?;
+        }
+
+        self.size = NonZeroUsize::new(size).unwrap();
+        Ok(self.address)
+    }
+
+    // Extend the backing file, returning the old and new lengths of the mapping.
+    fn resize(&mut self, size: usize) -> crate::Result<(usize, usize)> {
+        assert!(
+            size >= self.size.get(),
+            "Cannot shrink mapping of size {:#x} to {size:#x}",
+            self.size,
+        );
+
+        self.file.resize(NonZeroUsize::new(size).unwrap())?;
+        Ok((
+            self.size.get().next_multiple_of(Page::SIZE),
+            size.next_multiple_of(Page::SIZE),
+        ))
+    }
+
+    /// Swap the pages backing `self` and `other` while keeping their addresses,
+    /// so readers using a stable base pointer observe the other mapping's
+    /// contents after a single `mremap`. Both mappings must be the same size.
+    ///
+    /// On failure, both mappings keep their own pages.
+    ///
+    /// Note: relies on `MREMAP_DONTUNMAP`, which requires Linux 5.7 for private
+    /// anonymous mappings and 5.13 for shared mappings such as shm segments. Older
+    /// kernels fail with `EINVAL`.
+    pub fn swap_with(&mut self, other: &mut Raw) -> crate::Result<()> {
+        // https://github.com/torvalds/linux/blob/0c559323bbaabee7346c12e74b497e283aaafef5/include/uapi/linux/mman.h#L9
+        const MREMAP_DONTUNMAP: libc::c_int = 4;
+
+        // SAFETY: caller must ensure both ranges are mappings of `size` bytes.
+        unsafe fn remap(
+            from: *mut ffi::c_void,
+            to: *mut ffi::c_void,
+            size: usize,
+            flags: libc::c_int,
+        ) -> crate::Result<()> {
+            unsafe {
+                try_libc!(libc::mremap(
+                    from,
+                    size,
+                    size,
+                    libc::MREMAP_MAYMOVE | libc::MREMAP_FIXED | flags,
+                    to,
+                ))
+            }?;
+            Ok(())
+        }
+
+        let size = self.size.get().next_multiple_of(Page::SIZE);
+        if size != other.size.get().next_multiple_of(Page::SIZE) {
+            return Err(crate::Error::SizeMismatch {
+                expected: self.size.get(),
+                actual: other.size.get(),
+            });
+        }
+
+        let temp = unsafe {
+            try_libc!(libc::mmap64(
+                ptr::null_mut(),
+                size,
+                libc::PROT_NONE,
+                libc::MAP_ANONYMOUS | libc::MAP_PRIVATE,
+                -1,
+                0,
+            ))
+        }?;
+
+        let this = self.address.as_ptr().cast::<ffi::c_void>();
+        let that = other.address.as_ptr().cast::<ffi::c_void>();
+
+        // Move `self`'s pages back from `temp` after a later step failed.
+        let restore = |error: crate::Error| unsafe {
+            if let Err(undo) = remap(temp, this, size, 0) {
+                log::error!("Failed to restore mapping after failed swap: {undo}");
+                libc::munmap(temp, size);
+            }
+            Err(error)
+        };
+
+        // `MREMAP_DONTUNMAP` keeps the source range mapped, so `self.address`
+        // is never unmapped: it switches directly from its old to its new pages.
+        unsafe {
+            if let Err(error) = remap(this, temp, size, MREMAP_DONTUNMAP) {
+                libc::munmap(temp, size);
+                return Err(error);
+            }
+
+            if let Err(error) = remap(that, this, size, MREMAP_DONTUNMAP) {
+                return restore(error);
+            }
+
+            if let Err(error) = remap(temp, that, size, 0) {
+                // Move `other`'s pages back from `this` before restoring `self`'s.
+                if let Err(undo) = remap(this, that, size, 0) {
+                    log::error!("Failed to restore mapping after failed swap: {undo}");
+                }
+                return restore(error);
+            }
+        }
+
+        // Every field except the address describes the pages, so follows them.
+        mem::swap(self, other);
+        mem::swap(&mut self.address, &mut other.address);
+        Ok(())
+    }
+
+    /// View the whole mapping as possibly uninitialized bytes, e.g. for a
+    /// producer filling a segment incrementally.
+    pub fn as_uninit_slice(&mut self) -> &mut [MaybeUninit<u8>] {
+        unsafe { slice::from_raw_parts_mut(self.address.as_ptr().cast(), self.size.get()) }
+    }
+
+    /// Whether the segment is mapped without write access, either because it was
+    /// attached read-only or because this process lacks write permission.
+    pub fn is_readonly(&self) -> bool {
+        self.file.is_readonly()
+    }
+
+    /// Revoke write access to the mapping, so that accidental writes fault
+    /// with `SIGSEGV`.
+    pub fn protect_readonly(&mut self) -> crate::Result<()> {
+        unsafe {
+            try_libc!(libc::mprotect(
+                self.address.as_ptr().cast(),
+                self.size.get().next_multiple_of(Page::SIZE),
+                Protection::ReadOnly.prot(),
+            ))
+        }?;
+        self.file.set_protection(Protection::ReadOnly);
+        Ok(())
+    }
+
+    /// Identity of the backing object, or `None` for anonymous mappings.
+    pub fn identity(&self) -> crate::Result<Option<SegmentIdentity>> {
+        self.file.stat().map(|stat| {
+            stat.map(|stat| SegmentIdentity {
+                dev: stat.st_dev,
+                ino: stat.st_ino,
+            })
+        })
+    }
+
+    /// Copy the contents to `dst` at its current offset, e.g. to snapshot a segment
+    /// to disk, returning the number of bytes copied.
+    ///
+    /// Uses `copy_file_range` to copy within the kernel, falling back to writing
+    /// from the mapping for anonymous mappings or when the kernel refuses
+    /// (e.g. across filesystems on older kernels).
+    pub fn copy_to_file(&self, dst: &std::fs::File) -> crate::Result<u64> {
+        let size = self.size.get();
+        let mut copied = 0;
+
+        if self.file.as_raw_fd() >= 0 {
+            let mut offset = self.file.offset();
+            while copied < size {
+                match unsafe {
+                    try_libc!(libc::copy_file_range(
+                        self.file.as_raw_fd(),
+                        &mut offset,
+                        dst.as_raw_fd(),
+                        ptr::null_mut(),
+                        size - copied,
+                        0,
+                    ))
+                } {
+                    Ok(0) => break,
+                    Ok(count) => copied += count as usize,
+                    Err(crate::Error::Libc { source, .. })
+                        if copied == 0
+                            && matches!(
+                                source.raw_os_error(),
+                                Some(libc::EXDEV | libc::EINVAL | libc::EOPNOTSUPP | libc::ENOSYS)
+                            ) =>
+                    {
+                        break;
+                    }
+                    Err(error) => return Err(error),
+                }
+            }
+        }
+
+        if copied < size {
+            let remaining = unsafe {
+                slice::from_raw_parts(
+                    self.address.as_ptr().cast::<u8>().add(copied),
+                    size - copied,
+                )
+            };
+
+            let mut dst = dst;
+            dst.write_all(remaining)
+                .map_err(|source| crate::Error::Libc {
+                    name: "write",
+                    source,
+                })?;
+        }
+
+        Ok(size as u64)
+    }
+
+    /// Opt the mapping in or out of kernel same-page merging (KSM) with
+    /// `MADV_MERGEABLE` or `MADV_UNMERGEABLE`, e.g. to deduplicate many
+    /// similar per-tenant segments.
+    ///
+    /// Note: only helps when pages are identical and rarely written, since
+    /// writing a merged page triggers a copy. Requires `CONFIG_KSM` and
+    /// `/sys/kernel/mm/ksm/run` to be enabled.
+    pub fn set_mergeable(&self, on: bool) -> crate::Result<()> {
+        crate::backend::madvise(
+            self.address.as_ptr().cast(),
+            self.size.get(),
+            match on {
+                true => libc::MADV_MERGEABLE,
+                false => libc::MADV_UNMERGEABLE,
+            },
+        )
+    }
+
+    /// Hint the kernel about how the mapping will be accessed, without remapping.
+    pub fn advise(&self, advice: Advice) -> crate::Result<()> {
+        crate::backend::madvise(
+            self.address.as_ptr().cast(),
+            self.size.get().next_multiple_of(Page::SIZE),
+            advice.advice(),
+        )
+    }
+
+    /// Fault in and lock the mapping's pages into memory, so they cannot be
+    /// swapped out. Fails with `Error::MemlockLimit` if this would exceed
+    /// `RLIMIT_MEMLOCK` without `CAP_IPC_LOCK`.
+    pub fn lock(&self) -> crate::Result<()> {
+        crate::backend::mlock(
+            self.address.as_ptr().cast(),
+            self.size.get().next_multiple_of(Page::SIZE),
+        )
+    }
+
+    /// Allow the mapping's pages to be swapped out again.
+    pub fn unlock(&self) -> crate::Result<()> {
+        unsafe {
+            try_libc!(libc::munlock(
+                self.address.as_ptr().cast(),
+                self.size.get().next_multiple_of(Page::SIZE),
+            ))
+        }?;
+        Ok(())
+    }
+
+    /// Apply advisory lock `operation` (e.g. `LOCK_EX`) to the backing file.
+    /// A no-op for anonymous mappings, which no other process can open.
+    pub(crate) fn flock(&self, operation: libc::c_int) -> crate::Result<()> {
+        let Some(fd) = self.as_fd() else {
+            return Ok(());
+        };
+
+        unsafe { try_libc!(libc::flock(fd.as_raw_fd(), operation)) }?;
+        Ok(())
+    }
+
+    /// Write dirty pages back to the backing file, blocking until complete.
+    /// A no-op for anonymous mappings, which have no backing file.
+    pub fn flush(&self) -> crate::Result<()> {
+        self.msync(0, self.size.get(), libc::MS_SYNC)
+    }
+
+    /// Schedule dirty pages to be written back to the backing file without
+    /// waiting. See [`Raw::flush`].
+    pub fn flush_async(&self) -> crate::Result<()> {
+        self.msync(0, self.size.get(), libc::MS_ASYNC)
+    }
+
+    /// Like [`Raw::flush`], but only for the pages overlapping `len` bytes
+    /// starting at `offset`.
+    pub fn flush_range(&self, offset: usize, len: usize) -> crate::Result<()> {
+        assert!(
+            offset + len <= self.size.get(),
+            "Flush range {offset:#x}+{len:#x} out of bounds for mapping of size {:#x}",
+            self.size,
+        );
+
+        let start = offset / Page::SIZE * Page::SIZE;
+        self.msync(start, offset + len - start, libc::MS_SYNC)
+    }
+
+    fn msync(&self, offset: usize, len: usize, flags: libc::c_int) -> crate::Result<()> {
+        if self.as_fd().is_none() || len == 0 {
+            return Ok(());
+        }
+
+        unsafe {
+            try_libc!(libc::msync(
+                self.address.byte_add(offset).as_ptr().cast(),
+                len.next_multiple_of(Page::SIZE),
+                flags,
+            ))
+        }?;
+        Ok(())
+    }
+
+    /// Fraction of pages currently resident in memory, e.g. for reporting
+    /// progress while a lazily loaded segment warms up.
+    pub fn residency(&self) -> crate::Result<f64> {
+        let (resident, total) = self.resident_pages()?;
+        Ok(resident as f64 / total as f64)
+    }
+
+    /// Number of resident pages and total number of pages in the mapping, via `mincore`,
+    /// e.g. to check that [`Populate`](crate::Populate) prefaulted the mapping.
+    pub fn resident_pages(&self) -> crate::Result<(usize, usize)> {
+        let total = self.size.get().div_ceil(Page::SIZE);
+        let mut pages = vec![0u8; total];
+        unsafe {
+            try_libc!(libc::mincore(
+                self.address.as_ptr().cast(),
+                self.size.get(),
+                pages.as_mut_ptr(),
+            ))
+        }?;
+
+        // Only the least significant bit is defined.
+        let resident = pages.iter().filter(|page| *page & 1 != 0).count();
+        Ok((resident, total))
+    }
+
+    /// Make instructions written to byte offsets `range` visible to instruction fetch,
+    /// e.g. after JIT compiling into a mapping with [`Protection::ReadWriteExec`].
+    ///
+    /// No-op on x86, whose instruction cache is coherent with data writes.
+    ///
+    /// Panics if `range` exceeds the mapping.
+    pub fn flush_icache(&self, range: Range<usize>) {
+        assert!(
+            range.start <= range.end && range.end <= self.size.get(),
+            "Flush range {range:#x?} must be within mapping of size {:#x}",
+            self.size,
+        );
+
+        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+        unsafe {
+            // Provided by compiler-rt and libgcc.
+            unsafe extern "C" {
+                fn __clear_cache(start: *mut ffi::c_char, end: *mut ffi::c_char);
+            }
+
+            let start = self.address.cast::<ffi::c_char>().as_ptr();
+            __clear_cache(start.add(range.start), start.add(range.end));
+        }
+
+        // Still prevent the compiler from reordering writes past the flush.
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::Release);
+    }
+
+    /// Hand the pages at byte offsets `range` to a pipe without copying, via `vmsplice`
+    /// with `SPLICE_F_GIFT`. Returns the number of bytes spliced, which may be less
+    /// than requested if the pipe is full.
+    ///
+    /// Gifted pages are referenced directly by the pipe, so they must not be modified
+    /// until the reader has consumed them, or the reader may observe the modification.
+    ///
+    /// Panics if `range` is not page-aligned or exceeds the mapping.
+    pub fn splice_to(&self, pipe: BorrowedFd<'_>, range: Range<usize>) -> crate::Result<usize> {
+        assert!(
+            range.start % Page::SIZE == 0
+                && range.end % Page::SIZE == 0
+                && range.end <= self.size.get().next_multiple_of(Page::SIZE),
+            "Splice range {range:#x?} must be page-aligned and within mapping of size {:#x}",
+            self.size,
+        );
+
+        let iov = libc::iovec {
+            iov_base: unsafe { self.address.byte_add(range.start) }
+                .as_ptr()
+                .cast(),
+            iov_len: range.len(),
+        };
+
+        let spliced = unsafe {
+            try_libc!(libc::vmsplice(
+                pipe.as_raw_fd(),
+                &iov,
+                1,
+                libc::SPLICE_F_GIFT
+            ))
+        }?;
+
+        Ok(spliced as usize)
+    }
+
+    /// Unmap the segment but leave it for other processes to attach to.
+    /// Unlike dropping, this never unlinks, even for segments created
+    /// with `unlink_on_drop`.
+    pub fn detach(mut self) {
+        self.unlink_on_drop = false;
+    }
+
     pub fn unlink(&mut self) -> crate::Result<()> {
-        crate::backend::Shm.unlink(&self.name)
+        match &self.name {
+            Some(name) => {
+                // Always valid, since constructed from a `String`.
+                let name = name.to_str().expect("Raw name is not UTF-8");
+                crate::cleanup::untrack(name);
+                self.backend.unlink(name)
+            }
+            None => Ok(()),
+        }
     }
 }
 
 impl Drop for Raw {
     fn drop(&mut self) {
+        if self.unlink_on_drop && self.file.is_create() {
+            if let Err(error) = self.unlink() {
+                log::error!("Failed to unlink {:?}: {}", self.name, error);
+            }
+        }
+
         if let Err(error) = unsafe {
             crate::try_libc!(libc::munmap(
                 self.address.as_ptr().cast::<ffi::c_void>(),
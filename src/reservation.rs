@@ -1,13 +1,21 @@
 use core::ffi;
+use core::mem;
 use core::num::NonZeroUsize;
 use core::ptr;
 use core::ptr::NonNull;
 
+use crate::HugePage;
+use crate::Numa;
 use crate::Page;
+use crate::Populate;
+use crate::backend::File;
+use crate::backend::MapOptions;
 use crate::try_libc;
 
 pub struct Reservation<const SIZE: usize> {
     address: NonNull<Page>,
+    // Whether mappings placed into the reservation reserve swap space.
+    reserve: bool,
 }
 
 impl<const SIZE: usize> Reservation<SIZE> {
@@ -18,7 +26,53 @@ impl<const SIZE: usize> Reservation<SIZE> {
     // and then overwrite it later via `mmap` with `MMAP_FIXED`.
     pub fn new() -> crate::Result<Self> {
         let address = Self::mmap(Self::SIZE)?;
-        Ok(Self { address })
+        Ok(Self {
+            address,
+            reserve: true,
+        })
+    }
+
+    /// Reserve address space aligned to `align`, e.g. 2 MiB for huge pages.
+    ///
+    /// Panics if `align` is not a power of two or not a multiple of the page size.
+    pub fn with_alignment(align: usize) -> crate::Result<Self> {
+        assert!(
+            align.is_power_of_two() && align % Page::SIZE == 0,
+            "Reservation alignment must be a power of two multiple of {}, got {align:#x}",
+            Page::SIZE,
+        );
+
+        // Over-reserve so an aligned range must fit, then trim both ends.
+        let size = SIZE.next_multiple_of(Page::SIZE);
+        let padded = NonZeroUsize::new(size + align - Page::SIZE).unwrap();
+        let address = Self::mmap(padded)?;
+
+        let start = address.addr().get();
+        let aligned = start.next_multiple_of(align);
+        let end = start + padded.get();
+
+        unsafe {
+            if aligned > start {
+                try_libc!(libc::munmap(address.as_ptr().cast(), aligned - start))?;
+            }
+            if end > aligned + size {
+                try_libc!(libc::munmap(
+                    address.byte_add(aligned - start + size).as_ptr().cast(),
+                    end - aligned - size,
+                ))?;
+            }
+        }
+
+        Ok(Self {
+            address: unsafe { address.byte_add(aligned - start) },
+            reserve: true,
+        })
+    }
+
+    /// Reserve address space aligned to `huge_page`, so it can later be backed
+    /// by huge pages with `MAP_FIXED`. See [`Reservation::map_huge`].
+    pub fn with_huge_page(huge_page: HugePage) -> crate::Result<Self> {
+        Self::with_alignment(huge_page.size())
     }
 
     pub fn new_contiguous<const COUNT: usize>() -> crate::Result<[Self; COUNT]> {
@@ -26,26 +80,82 @@ impl<const SIZE: usize> Reservation<SIZE> {
         let address = Self::mmap(total)?;
         Ok(std::array::from_fn(|i| Self {
             address: unsafe { address.byte_add(SIZE * i) },
+            reserve: true,
         }))
     }
 
     fn mmap(size: NonZeroUsize) -> crate::Result<NonNull<Page>> {
-        unsafe {
+        let address = unsafe {
             try_libc!(libc::mmap64(
                 ptr::null_mut(),
                 size.get(),
                 libc::PROT_NONE,
-                libc::MAP_ANONYMOUS | libc::MAP_PRIVATE,
+                // Never accessible, so never needs swap space.
+                libc::MAP_ANONYMOUS | libc::MAP_PRIVATE | libc::MAP_NORESERVE,
                 -1,
                 0,
             ))
         }
         .map(NonNull::new)
         .map(Option::unwrap)
-        .map(|address| address.cast::<Page>())
+        .map(|address| address.cast::<Page>())?;
+
+        #[cfg(debug_assertions)]
+        Self::verify(address, size);
+
+        Ok(address)
+    }
+
+    // Check that the kernel handed back genuinely inaccessible address space,
+    // since placement via `MAP_FIXED` later relies on it being otherwise unused.
+    #[cfg(debug_assertions)]
+    fn verify(address: NonNull<Page>, size: NonZeroUsize) {
+        let start = address.addr().get();
+        let end = start + size.get();
+        // Skip the check where /proc isn't mounted, e.g. in some sandboxes and containers.
+        let maps = match std::fs::read_to_string("/proc/self/maps") {
+            Ok(maps) => maps,
+            Err(error) => {
+                log::debug!("Skipping reservation check, failed to read /proc/self/maps: {error}");
+                return;
+            }
+        };
+
+        let reserved = maps.lines().any(|line| {
+            let mut fields = line.split_whitespace();
+            let (Some(range), Some(permissions)) = (fields.next(), fields.next()) else {
+                return false;
+            };
+            let Some((low, high)) = range.split_once('-') else {
+                return false;
+            };
+            let (Ok(low), Ok(high)) = (
+                usize::from_str_radix(low, 16),
+                usize::from_str_radix(high, 16),
+            ) else {
+                return false;
+            };
+
+            low <= start && end <= high && permissions.starts_with("---")
+        });
+
+        assert!(
+            reserved,
+            "Reservation {start:#x}..{end:#x} is not an inaccessible mapping"
+        );
     }
 
-    pub fn unmap(&self) -> crate::Result<()> {
+    /// Unmap the reservation, including anything mapped into it, reporting
+    /// failure instead of logging it like `drop`.
+    pub fn unmap(self) -> crate::Result<()> {
+        let result = self.munmap();
+        mem::forget(self);
+        result
+    }
+
+    // Each reservation, including those from `new_contiguous`, owns exactly its
+    // own `SIZE` bytes, so sibling reservations never unmap each other's ranges.
+    fn munmap(&self) -> crate::Result<()> {
         unsafe {
             crate::try_libc!(libc::munmap(
                 self.address.as_ptr().cast::<ffi::c_void>(),
@@ -55,6 +165,97 @@ impl<const SIZE: usize> Reservation<SIZE> {
         Ok(())
     }
 
+    /// Place `file` at page `offset_pages` into this reservation with `MAP_FIXED`,
+    /// e.g. to extend a growable shared heap that stays contiguous. Fails with
+    /// `Error::ReservationOverflow` if the mapping doesn't fit within the reservation.
+    pub fn map_into(
+        &self,
+        file: File,
+        offset_pages: usize,
+        numa: Option<Numa>,
+        populate: Option<Populate>,
+    ) -> crate::Result<NonNull<Page>> {
+        let offset =
+            offset_pages
+                .checked_mul(Page::SIZE)
+                .ok_or(crate::Error::ReservationOverflow {
+                    offset: usize::MAX,
+                    size: file.size().get(),
+                    capacity: SIZE,
+                })?;
+
+        self.place(&file, offset, Page::SIZE, numa, populate)
+    }
+
+    /// Place `file` at byte `offset` into this reservation with `MAP_FIXED`, e.g. a
+    /// hugetlbfs-backed file into a reservation from [`Reservation::with_alignment`],
+    /// to build a growable huge page arena.
+    ///
+    /// Both the target address and the file size must be multiples of `page_size`
+    /// (e.g. 2 MiB or 1 GiB), and the mapping must fit within the reservation.
+    pub fn map_huge(
+        &self,
+        file: &File,
+        offset: usize,
+        page_size: usize,
+        numa: Option<Numa>,
+        populate: Option<Populate>,
+    ) -> crate::Result<NonNull<Page>> {
+        self.place(file, offset, page_size, numa, populate)
+    }
+
+    pub(crate) fn place(
+        &self,
+        file: &File,
+        offset: usize,
+        page_size: usize,
+        numa: Option<Numa>,
+        populate: Option<Populate>,
+    ) -> crate::Result<NonNull<Page>> {
+        let size = file.size().get();
+        if offset.checked_add(size).is_none_or(|end| end > SIZE) {
+            return Err(crate::Error::ReservationOverflow {
+                offset,
+                size,
+                capacity: SIZE,
+            });
+        }
+
+        let address = unsafe { self.address.byte_add(offset) };
+        for value in [address.addr().get(), size] {
+            if value % page_size != 0 {
+                return Err(crate::Error::Alignment {
+                    required: page_size,
+                    actual: 1 << value.trailing_zeros(),
+                });
+            }
+        }
+
+        // SAFETY: target range lies within this reservation.
+        unsafe {
+            file.map_with(MapOptions {
+                address: Some(address),
+                reserve: self.reserve,
+                numa,
+                populate,
+                ..MapOptions::default()
+            })
+        }
+    }
+
+    /// Place later mappings with `MAP_NORESERVE` if `reserve` is false, like the
+    /// reserved window itself, so a huge sparse region only commits the pages
+    /// that are actually touched. See the `reserve` option of [`File::map`].
+    pub fn with_reserve(mut self, reserve: bool) -> Self {
+        self.reserve = reserve;
+        self
+    }
+
+    /// Largest power of two dividing the start address.
+    pub fn alignment(&self) -> usize {
+        1 << self.address.addr().trailing_zeros()
+    }
+
     pub fn start(&self) -> NonNull<Page> {
         self.address
     }
@@ -63,3 +264,16 @@ impl<const SIZE: usize> Reservation<SIZE> {
         unsafe { self.address.byte_add(SIZE) }
     }
 }
+
+impl<const SIZE: usize> Drop for Reservation<SIZE> {
+    fn drop(&mut self) {
+        if let Err(error) = self.munmap() {
+            log::error!(
+                "Failed to munmap reservation {:#x?} ({:#x}): {}",
+                self.address,
+                SIZE,
+                error
+            );
+        }
+    }
+}
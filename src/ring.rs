@@ -0,0 +1,389 @@
+use core::ptr;
+use core::sync::atomic;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
+
+use crate::Raw;
+
+/// Fixed-capacity ring of byte records shared across processes, e.g. for
+/// cross-process tracing. Once the ring is full, new records overwrite the
+/// oldest ones.
+///
+/// Records are framed with their length and a commit flag, so readers never
+/// observe a partially written record, and readers that fall more than a
+/// lap behind skip ahead instead of returning overwritten data.
+///
+/// Records never straddle the end of the ring, so each lap begins with a
+/// record, and readers resume from the start of the current lap after
+/// falling behind.
+///
+/// Note: a writer that dies between reserving and committing a record stalls
+/// readers at that record until writers lap it.
+pub struct Log {
+    inner: Raw,
+    capacity: u64,
+}
+
+#[repr(C)]
+struct Control {
+    // Logical end of the space reserved by writers, which increases forever.
+    // The physical offset of a logical position is modulo the capacity.
+    head: AtomicU64,
+}
+
+#[repr(C)]
+struct Frame {
+    // Logical position of the record, tagged with `COMMITTED` and `PADDING`.
+    stamp: AtomicU64,
+    length: u32,
+}
+
+unsafe impl Sync for Log {}
+unsafe impl Send for Log {}
+
+#[cfg(feature = "builder")]
+#[bon::bon]
+impl Log {
+    /// All processes must agree on `capacity`, the number of bytes available
+    /// for records. Rounded up to a multiple of 16 bytes. Fails with
+    /// `Error::ZeroCapacity` if `capacity` is zero.
+    #[builder]
+    pub fn new(
+        name: String,
+        #[builder(default)] create: bool,
+        capacity: usize,
+    ) -> crate::Result<Self> {
+        Self::named(name, capacity, create)
+    }
+}
+
+impl Log {
+    const HEADER: u64 = size_of::<Frame>() as u64;
+    const ALIGN: u64 = Self::HEADER;
+    const OFFSET: usize = size_of::<Control>().next_multiple_of(Self::ALIGN as usize);
+
+    // Positions are multiples of `ALIGN`, leaving the low bits for flags.
+    const COMMITTED: u64 = 1 << 0;
+    const PADDING: u64 = 1 << 1;
+    const FLAGS: u64 = Self::COMMITTED | Self::PADDING;
+
+    /// Shorthand for `Log::builder().name(name).capacity(capacity).create(create).build()`.
+    pub fn named(name: String, capacity: usize, create: bool) -> crate::Result<Self> {
+        if capacity == 0 {
+            return Err(crate::Error::ZeroCapacity);
+        }
+
+        let capacity = capacity.next_multiple_of(Self::ALIGN as usize);
+        let inner = Raw::named(name, Self::OFFSET + capacity, create)?;
+        Ok(Self {
+            inner,
+            capacity: capacity as u64,
+        })
+    }
+
+    /// Append `record`, overwriting the oldest records if necessary. Returns
+    /// `false` if `record` is too large to ever fit in the ring.
+    pub fn append(&self, record: &[u8]) -> bool {
+        let Ok(length) = u32::try_from(record.len()) else {
+            return false;
+        };
+
+        let size = (Self::HEADER + u64::from(length)).next_multiple_of(Self::ALIGN);
+
+        // Leave room for the next record's header, which is cleared below.
+        if size + Self::HEADER > self.capacity {
+            return false;
+        }
+
+        let control = self.control();
+        let mut head = control.head.load(Ordering::Relaxed);
+        let (padding, start) = loop {
+            // Pad out the rest of the lap if the record doesn't fit before the end of the ring.
+            let remaining = self.capacity - head % self.capacity;
+            let (padding, start) = match size > remaining {
+                true => (Some(head), head + remaining),
+                false => (None, head),
+            };
+
+            match control.head.compare_exchange_weak(
+                head,
+                start + size,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break (padding, start),
+                Err(actual) => head = actual,
+            }
+        };
+
+        // Pairs with the fence in `LogIter::next`, so readers copying records
+        // that this write overwrites also observe the new head.
+        atomic::fence(Ordering::Release);
+
+        unsafe {
+            let frame = self.frame(start);
+            (&raw mut (*frame).length).write(length);
+            ptr::copy_nonoverlapping(
+                record.as_ptr(),
+                frame.cast::<u8>().add(Self::HEADER as usize),
+                record.len(),
+            );
+        }
+
+        self.clear(start + size);
+        self.commit(start, 0);
+
+        if let Some(padding) = padding {
+            unsafe {
+                (&raw mut (*self.frame(padding)).length)
+                    .write((start - padding - Self::HEADER) as u32)
+            };
+            self.commit(padding, Self::PADDING);
+        }
+
+        true
+    }
+
+    /// Iterate over committed records, starting from the oldest record not yet overwritten.
+    ///
+    /// Note: records from the previous lap that have not been overwritten
+    /// yet are skipped, since there is no record boundary to resume from.
+    pub fn iter(&self) -> LogIter<'_> {
+        let head = self.control().head.load(Ordering::Acquire);
+        let position = match head + Self::HEADER <= self.capacity {
+            true => 0,
+            false => self.lap(head),
+        };
+
+        self.iter_from(position)
+    }
+
+    /// Iterate over committed records starting at `position`, e.g. from
+    /// [`LogIter::position`] to resume where a previous iterator stopped.
+    pub fn iter_from(&self, position: u64) -> LogIter<'_> {
+        LogIter {
+            log: self,
+            position,
+            overwritten: false,
+        }
+    }
+
+    pub fn unlink(&mut self) -> crate::Result<()> {
+        self.inner.unlink()
+    }
+
+    // Mark the header at `position` as uncommitted, unless the writer that
+    // reserved it has already written its stamp. Otherwise, stale bytes from
+    // the previous lap could be mistaken for a committed record.
+    fn clear(&self, position: u64) {
+        let stamp = unsafe { &(*self.frame(position)).stamp };
+        let mut current = stamp.load(Ordering::Relaxed);
+        while current & !Self::FLAGS != position {
+            match stamp.compare_exchange_weak(
+                current,
+                position,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    fn commit(&self, position: u64, flags: u64) {
+        let stamp = unsafe { &(*self.frame(position)).stamp };
+        stamp.store(position | flags | Self::COMMITTED, Ordering::Release);
+    }
+
+    // Whether the record at `position` may have been overwritten, given
+    // the current head. Includes the header cleared past the head.
+    fn is_overwritten(&self, position: u64, head: u64) -> bool {
+        head + Self::HEADER > position + self.capacity
+    }
+
+    // Logical position of the start of the lap containing `position`.
+    fn lap(&self, position: u64) -> u64 {
+        position - position % self.capacity
+    }
+
+    fn control(&self) -> &Control {
+        unsafe { self.inner.address().cast::<Control>().as_ref() }
+    }
+
+    fn frame(&self, position: u64) -> *mut Frame {
+        unsafe {
+            self.inner
+                .address()
+                .cast::<u8>()
+                .add(Self::OFFSET + (position % self.capacity) as usize)
+                .cast::<Frame>()
+                .as_ptr()
+        }
+    }
+}
+
+impl<'log> IntoIterator for &'log Log {
+    type Item = Vec<u8>;
+    type IntoIter = LogIter<'log>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over committed records, which copies each record out since
+/// writers may overwrite it at any time. Ends at the first record that
+/// isn't committed yet.
+pub struct LogIter<'log> {
+    log: &'log Log,
+    position: u64,
+    overwritten: bool,
+}
+
+impl LogIter<'_> {
+    /// Logical position of the next record, to resume from with [`Log::iter_from`].
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Whether any records were overwritten before this iterator could read them.
+    pub fn overwritten(&self) -> bool {
+        self.overwritten
+    }
+
+    // Skip to the start of the current lap, which is the oldest record boundary
+    // that hasn't been overwritten.
+    fn skip(&mut self, head: u64) {
+        self.position = self.log.lap(head);
+        self.overwritten = true;
+    }
+}
+
+impl Iterator for LogIter<'_> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let log = self.log;
+
+        loop {
+            let frame = log.frame(self.position);
+            let stamp = unsafe { (*frame).stamp.load(Ordering::Acquire) };
+
+            if stamp & !Log::FLAGS != self.position || stamp & Log::COMMITTED == 0 {
+                let head = log.control().head.load(Ordering::Acquire);
+                match log.is_overwritten(self.position, head) {
+                    true => {
+                        self.skip(head);
+                        continue;
+                    }
+                    false => return None,
+                }
+            }
+
+            let length = unsafe { (&raw const (*frame).length).read() };
+
+            // A length that runs past the end of the ring can only have been
+            // read from a record that was overwritten after its stamp was read.
+            if u64::from(length) > log.capacity - self.position % log.capacity - Log::HEADER {
+                let head = log.control().head.load(Ordering::Acquire);
+                self.skip(head);
+                continue;
+            }
+
+            let next =
+                self.position + (Log::HEADER + u64::from(length)).next_multiple_of(Log::ALIGN);
+
+            if stamp & Log::PADDING != 0 {
+                self.position = next;
+                continue;
+            }
+
+            let mut record = Vec::with_capacity(length as usize);
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    frame.cast::<u8>().add(Log::HEADER as usize),
+                    record.as_mut_ptr(),
+                    length as usize,
+                );
+                record.set_len(length as usize);
+            }
+
+            // Check that no writer overwrote the record while it was being copied.
+            atomic::fence(Ordering::Acquire);
+            let head = log.control().head.load(Ordering::Relaxed);
+            if log.is_overwritten(self.position, head) {
+                self.skip(head);
+                continue;
+            }
+
+            self.position = next;
+            return Some(record);
+        }
+    }
+}
+
+impl crate::Teardown for Log {
+    fn unlink(&mut self) -> crate::Result<()> {
+        Log::unlink(self)
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use super::Log;
+    use crate::testing;
+
+    fn log(suffix: &str, capacity: usize) -> Log {
+        Log::builder()
+            .name(testing::name("log", suffix))
+            .create(true)
+            .capacity(capacity)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn append_and_iterate() {
+        let mut log = log("iterate", 256);
+        assert!(log.append(b"hello"));
+        assert!(log.append(b""));
+        assert!(log.append(b"world"));
+
+        let records = log.iter().collect::<Vec<_>>();
+        assert_eq!(records, [&b"hello"[..], b"", b"world"]);
+
+        log.unlink().unwrap();
+    }
+
+    #[test]
+    fn reject_oversized() {
+        let mut log = log("oversized", 64);
+        assert!(!log.append(&[0; 64]));
+        assert!(log.iter().next().is_none());
+        log.unlink().unwrap();
+    }
+
+    #[test]
+    fn overwrite_oldest() {
+        // Each record takes 32 bytes, so four fit in a lap.
+        let mut log = log("overwrite", 128);
+        let mut reader = log.iter();
+
+        for index in 0..10u8 {
+            assert!(log.append(&[index; 8]));
+        }
+
+        // The reader fell behind, so it resumes from the start of the current lap.
+        let records = reader.by_ref().collect::<Vec<_>>();
+        assert!(reader.overwritten());
+        assert_eq!(records, [[8; 8], [9; 8]]);
+
+        // Resuming picks up only new records.
+        let position = reader.position();
+        assert!(log.append(&[10; 8]));
+        assert_eq!(log.iter_from(position).collect::<Vec<_>>(), [[10; 8]]);
+
+        log.unlink().unwrap();
+    }
+}
@@ -0,0 +1,170 @@
+#[cfg(feature = "builder")]
+use core::mem::MaybeUninit;
+
+#[cfg(feature = "builder")]
+use crate::Numa;
+#[cfg(feature = "builder")]
+use crate::Populate;
+use crate::Shm;
+use crate::try_pthread;
+
+// From glibc's `pthread.h`, which the `libc` crate does not expose.
+#[cfg(feature = "builder")]
+const PTHREAD_RWLOCK_PREFER_WRITER_NONRECURSIVE_NP: libc::c_int = 2;
+
+#[cfg(feature = "builder")]
+unsafe extern "C" {
+    fn pthread_rwlockattr_setkind_np(
+        attr: *mut libc::pthread_rwlockattr_t,
+        pref: libc::c_int,
+    ) -> libc::c_int;
+}
+
+/// Process-shared `pthread` reader-writer lock, e.g. to protect a data
+/// structure mapped in another segment.
+pub struct RwLock {
+    inner: Shm<libc::pthread_rwlock_t>,
+}
+
+/// Releases shared access to the [`RwLock`] when dropped.
+pub struct RwLockReadGuard<'lock> {
+    lock: &'lock RwLock,
+}
+
+/// Releases exclusive access to the [`RwLock`] when dropped.
+pub struct RwLockWriteGuard<'lock> {
+    lock: &'lock RwLock,
+}
+
+unsafe impl Sync for RwLock {}
+unsafe impl Send for RwLock {}
+
+#[cfg(feature = "builder")]
+#[bon::bon]
+impl RwLock {
+    #[builder]
+    pub fn new(
+        name: String,
+        #[builder(default)] create: bool,
+        /// Block new readers while a writer is waiting, so that a steady stream
+        /// of readers cannot starve writers. By default, readers are preferred.
+        ///
+        /// Note: a thread holding a read lock must not recursively acquire
+        /// another, or it may deadlock with a waiting writer.
+        #[builder(default)]
+        prefer_writer: bool,
+        numa: Option<Numa>,
+        populate: Option<Populate>,
+    ) -> crate::Result<Self> {
+        let inner = Shm::<libc::pthread_rwlock_t>::builder()
+            .name(name)
+            .create(create)
+            .maybe_numa(numa)
+            .maybe_populate(populate)
+            .build()?;
+
+        if create {
+            let mut attr = unsafe {
+                let mut attr = MaybeUninit::<libc::pthread_rwlockattr_t>::zeroed();
+                try_pthread!(libc::pthread_rwlockattr_init(attr.as_mut_ptr()))?;
+                try_pthread!(libc::pthread_rwlockattr_setpshared(
+                    attr.as_mut_ptr(),
+                    libc::PTHREAD_PROCESS_SHARED
+                ))?;
+                if prefer_writer {
+                    try_pthread!(pthread_rwlockattr_setkind_np(
+                        attr.as_mut_ptr(),
+                        PTHREAD_RWLOCK_PREFER_WRITER_NONRECURSIVE_NP
+                    ))?;
+                }
+                attr.assume_init()
+            };
+
+            unsafe {
+                try_pthread!(libc::pthread_rwlock_init(inner.address().as_ptr(), &attr))?;
+            }
+
+            unsafe {
+                assert_eq!(libc::pthread_rwlockattr_destroy(&mut attr), 0);
+            }
+        }
+
+        Ok(Self { inner })
+    }
+}
+
+impl RwLock {
+    /// Block until shared access is acquired.
+    pub fn read(&self) -> crate::Result<RwLockReadGuard<'_>> {
+        unsafe { try_pthread!(libc::pthread_rwlock_rdlock(self.lock())) }?;
+        Ok(RwLockReadGuard { lock: self })
+    }
+
+    /// Block until exclusive access is acquired.
+    pub fn write(&self) -> crate::Result<RwLockWriteGuard<'_>> {
+        unsafe { try_pthread!(libc::pthread_rwlock_wrlock(self.lock())) }?;
+        Ok(RwLockWriteGuard { lock: self })
+    }
+
+    /// Acquire shared access if no writer holds the lock, returning `None` otherwise.
+    pub fn try_read(&self) -> crate::Result<Option<RwLockReadGuard<'_>>> {
+        Self::busy(unsafe { try_pthread!(libc::pthread_rwlock_tryrdlock(self.lock())) })
+            .map(|acquired| acquired.then(|| RwLockReadGuard { lock: self }))
+    }
+
+    /// Acquire exclusive access if the lock is not held, returning `None` otherwise.
+    pub fn try_write(&self) -> crate::Result<Option<RwLockWriteGuard<'_>>> {
+        Self::busy(unsafe { try_pthread!(libc::pthread_rwlock_trywrlock(self.lock())) })
+            .map(|acquired| acquired.then(|| RwLockWriteGuard { lock: self }))
+    }
+
+    pub fn unlink(&mut self) -> crate::Result<()> {
+        unsafe { try_pthread!(libc::pthread_rwlock_destroy(self.lock()))? }
+        self.inner.unlink()
+    }
+
+    // Map `EBUSY` to `Ok(false)`.
+    fn busy(result: crate::Result<()>) -> crate::Result<bool> {
+        match result {
+            Ok(()) => Ok(true),
+            Err(crate::Error::Libc { source, .. })
+                if source.raw_os_error() == Some(libc::EBUSY) =>
+            {
+                Ok(false)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    fn lock(&self) -> *mut libc::pthread_rwlock_t {
+        self.inner.address().as_ptr()
+    }
+
+    fn unlock(&self) {
+        if let Err(error) = unsafe { try_pthread!(libc::pthread_rwlock_unlock(self.lock())) } {
+            log::error!("Failed to unlock rwlock: {}", error);
+        }
+    }
+}
+
+impl Drop for RwLockReadGuard<'_> {
+    fn drop(&mut self) {
+        self.lock.unlock();
+    }
+}
+
+impl Drop for RwLockWriteGuard<'_> {
+    fn drop(&mut self) {
+        self.lock.unlock();
+    }
+}
+
+impl crate::Teardown for RwLock {
+    fn destroy(&mut self) -> crate::Result<()> {
+        unsafe { try_pthread!(libc::pthread_rwlock_destroy(self.lock())) }
+    }
+
+    fn unlink(&mut self) -> crate::Result<()> {
+        self.inner.unlink()
+    }
+}
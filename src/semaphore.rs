@@ -0,0 +1,178 @@
+use core::ffi;
+use core::mem;
+use core::ptr::NonNull;
+use core::time::Duration;
+use std::sync::OnceLock;
+
+use crate::Clock;
+use crate::try_libc;
+
+/// POSIX named counting semaphore, independent of any `pthread` object.
+///
+/// Names follow the same `/`-prefixed convention and length limit as
+/// [`backend::Shm`](crate::backend::Shm). The semaphore is closed when dropped.
+pub struct Semaphore {
+    name: String,
+    sem: NonNull<libc::sem_t>,
+}
+
+unsafe impl Sync for Semaphore {}
+unsafe impl Send for Semaphore {}
+
+#[cfg(feature = "builder")]
+#[bon::bon]
+impl Semaphore {
+    #[builder]
+    pub fn new(
+        name: String,
+        #[builder(default)] create: bool,
+        /// Initial count, only used when creating.
+        #[builder(default)]
+        value: u32,
+    ) -> crate::Result<Self> {
+        if create {
+            match Self::sem_unlink(&name) {
+                Ok(()) => log::info!("Unlinked stale semaphore: {}", name),
+                Err(error) if error.is_not_found() => (),
+                Err(error) => return Err(error),
+            }
+        }
+
+        let flags = match create {
+            true => libc::O_CREAT | libc::O_EXCL,
+            false => 0,
+        };
+
+        let mode: libc::c_uint = 0o666;
+        let sem = crate::backend::Shm::with_path(&name, |path| unsafe {
+            try_libc!(libc::sem_open(path.as_ptr(), flags, mode, value))
+        })?;
+
+        Ok(Self {
+            name,
+            sem: NonNull::new(sem).unwrap(),
+        })
+    }
+}
+
+impl Semaphore {
+    /// Block until the count is positive, then decrement it.
+    pub fn wait(&self) -> crate::Result<()> {
+        Self::retry(|| unsafe { try_libc!(libc::sem_wait(self.sem.as_ptr())) })
+    }
+
+    /// Decrement the count if it is positive, returning `false` otherwise.
+    pub fn try_wait(&self) -> crate::Result<bool> {
+        match unsafe { try_libc!(libc::sem_trywait(self.sem.as_ptr())) } {
+            Ok(_) => Ok(true),
+            Err(crate::Error::Libc { source, .. })
+                if source.raw_os_error() == Some(libc::EAGAIN) =>
+            {
+                Ok(false)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Like [`Semaphore::wait`], but give up after `timeout`. Returns `false`
+    /// if the wait timed out.
+    ///
+    /// The deadline is computed against the configured [`Clock`], except before
+    /// glibc 2.30, where `sem_clockwait` is unavailable and `sem_timedwait` only
+    /// supports `CLOCK_REALTIME`.
+    pub fn wait_timeout(&self, timeout: Duration) -> crate::Result<bool> {
+        let clockwait = sem_clockwait();
+        let clock = match clockwait {
+            Some(_) => Clock::current(),
+            None => Clock::Realtime,
+        };
+
+        let deadline = clock.deadline(timeout)?;
+        let result = Self::retry(|| unsafe {
+            match clockwait {
+                Some(sem_clockwait) => {
+                    try_libc!(sem_clockwait(self.sem.as_ptr(), clock.id(), &deadline))
+                }
+                None => try_libc!(libc::sem_timedwait(self.sem.as_ptr(), &deadline)),
+            }
+        });
+
+        match result {
+            Ok(()) => Ok(true),
+            Err(crate::Error::Libc { source, .. })
+                if source.raw_os_error() == Some(libc::ETIMEDOUT) =>
+            {
+                Ok(false)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Increment the count, waking a waiter if any.
+    pub fn post(&self) -> crate::Result<()> {
+        unsafe { try_libc!(libc::sem_post(self.sem.as_ptr())) }?;
+        Ok(())
+    }
+
+    /// Current count. Zero if there are waiters.
+    pub fn value(&self) -> crate::Result<u32> {
+        let mut value = 0;
+        unsafe { try_libc!(libc::sem_getvalue(self.sem.as_ptr(), &mut value)) }?;
+        Ok(value.max(0) as u32)
+    }
+
+    /// Remove the semaphore's name and close it. Processes that already
+    /// opened it can continue to use it.
+    pub fn unlink(self) -> crate::Result<()> {
+        Self::sem_unlink(&self.name)
+    }
+
+    fn sem_unlink(name: &str) -> crate::Result<()> {
+        crate::backend::Shm::with_path(name, |path| unsafe {
+            try_libc!(libc::sem_unlink(path.as_ptr()))
+        })?;
+        Ok(())
+    }
+
+    // Restart calls interrupted by a signal handler.
+    fn retry(mut apply: impl FnMut() -> crate::Result<libc::c_int>) -> crate::Result<()> {
+        loop {
+            match apply() {
+                Ok(_) => return Ok(()),
+                Err(crate::Error::Libc { source, .. })
+                    if source.raw_os_error() == Some(libc::EINTR) =>
+                {
+                    continue;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+impl crate::Teardown for Semaphore {
+    fn unlink(&mut self) -> crate::Result<()> {
+        Self::sem_unlink(&self.name)
+    }
+}
+
+impl Drop for Semaphore {
+    fn drop(&mut self) {
+        if let Err(error) = unsafe { try_libc!(libc::sem_close(self.sem.as_ptr())) } {
+            log::error!("Failed to close semaphore {}: {}", self.name, error);
+        }
+    }
+}
+
+type SemClockwait =
+    unsafe extern "C" fn(*mut libc::sem_t, libc::clockid_t, *const libc::timespec) -> libc::c_int;
+
+// Looked up at runtime, since linking against it directly would fail to load before glibc 2.30.
+fn sem_clockwait() -> Option<SemClockwait> {
+    static SEM_CLOCKWAIT: OnceLock<Option<SemClockwait>> = OnceLock::new();
+    *SEM_CLOCKWAIT.get_or_init(|| {
+        let symbol = unsafe { libc::dlsym(libc::RTLD_DEFAULT, c"sem_clockwait".as_ptr()) };
+        (!symbol.is_null())
+            .then(|| unsafe { mem::transmute::<*mut ffi::c_void, SemClockwait>(symbol) })
+    })
+}
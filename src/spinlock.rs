@@ -0,0 +1,150 @@
+use core::hint;
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::Ordering;
+use core::time::Duration;
+
+use crate::Clock;
+#[cfg(feature = "builder")]
+use crate::Numa;
+#[cfg(feature = "builder")]
+use crate::Populate;
+use crate::Shm;
+
+/// Process-shared spin lock, for short critical sections where the overhead
+/// of a `pthread` mutex isn't worth it.
+///
+/// Note: unlike a robust [`crate::Mutex`], a process that dies while holding
+/// the lock deadlocks every other process that tries to acquire it.
+pub struct SpinLock {
+    inner: Shm<AtomicU32>,
+}
+
+/// Releases the [`SpinLock`] when dropped.
+pub struct SpinLockGuard<'lock> {
+    lock: &'lock SpinLock,
+}
+
+unsafe impl Sync for SpinLock {}
+unsafe impl Send for SpinLock {}
+
+#[cfg(feature = "builder")]
+#[bon::bon]
+impl SpinLock {
+    /// Newly created segments are zeroed, which is the unlocked state.
+    #[builder]
+    pub fn new(
+        name: String,
+        #[builder(default)] create: bool,
+        numa: Option<Numa>,
+        populate: Option<Populate>,
+    ) -> crate::Result<Self> {
+        let inner = Shm::<AtomicU32>::builder()
+            .name(name)
+            .create(create)
+            .maybe_numa(numa)
+            .maybe_populate(populate)
+            .build()?;
+
+        Ok(Self { inner })
+    }
+}
+
+impl SpinLock {
+    const UNLOCKED: u32 = 0;
+    const LOCKED: u32 = 1;
+
+    // Upper bound on the number of spins between attempts.
+    const MAX_BACKOFF: u32 = 1 << 10;
+
+    /// Spin until the lock is acquired.
+    pub fn lock(&self) -> SpinLockGuard<'_> {
+        self.spin(|| true).unwrap()
+    }
+
+    /// Acquire the lock if it is not held, returning `None` otherwise.
+    pub fn try_lock(&self) -> Option<SpinLockGuard<'_>> {
+        self.state()
+            .compare_exchange(
+                Self::UNLOCKED,
+                Self::LOCKED,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .is_ok()
+            .then(|| SpinLockGuard { lock: self })
+    }
+
+    /// Like [`SpinLock::lock`], but give up after `timeout`, measured against
+    /// the configured [`Clock`].
+    pub fn lock_with_timeout(&self, timeout: Duration) -> crate::Result<Option<SpinLockGuard<'_>>> {
+        let clock = Clock::current();
+        let deadline = clock.deadline(timeout)?;
+        let mut error = None;
+        let guard = self.spin(|| match clock.is_past(&deadline) {
+            Ok(past) => !past,
+            Err(source) => {
+                error = Some(source);
+                false
+            }
+        });
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(guard),
+        }
+    }
+
+    pub fn unlink(&mut self) -> crate::Result<()> {
+        self.inner.unlink()
+    }
+
+    // Spin with exponential backoff while `proceed` returns `true`.
+    fn spin(&self, mut proceed: impl FnMut() -> bool) -> Option<SpinLockGuard<'_>> {
+        let mut backoff = 1;
+        loop {
+            if self
+                .state()
+                .compare_exchange_weak(
+                    Self::UNLOCKED,
+                    Self::LOCKED,
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                return Some(SpinLockGuard { lock: self });
+            }
+
+            // Wait for the lock to look free before retrying the write.
+            while self.state().load(Ordering::Relaxed) == Self::LOCKED {
+                if !proceed() {
+                    return None;
+                }
+
+                for _ in 0..backoff {
+                    hint::spin_loop();
+                }
+
+                backoff = (backoff * 2).min(Self::MAX_BACKOFF);
+            }
+        }
+    }
+
+    fn state(&self) -> &AtomicU32 {
+        unsafe { self.inner.get() }
+    }
+}
+
+impl Drop for SpinLockGuard<'_> {
+    fn drop(&mut self) {
+        self.lock
+            .state()
+            .store(SpinLock::UNLOCKED, Ordering::Release);
+    }
+}
+
+impl crate::Teardown for SpinLock {
+    fn unlink(&mut self) -> crate::Result<()> {
+        self.inner.unlink()
+    }
+}
@@ -0,0 +1,126 @@
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering;
+
+use crate::Shm;
+
+/// Fixed-capacity single-producer single-consumer queue of `N` elements,
+/// laid out to live in a [`Shm`] segment shared by the producing and
+/// consuming processes.
+///
+/// The kernel zeroes newly created segments, which is the empty state, so
+/// the creator need not initialize the ring. Attaching processes must not
+/// reinitialize it, e.g. with [`SpscRing::init`], after the other side has
+/// started using it.
+#[repr(C)]
+pub struct SpscRing<T, const N: usize> {
+    // Number of elements popped, written only by the consumer.
+    head: Padded,
+    // Number of elements pushed, written only by the producer.
+    tail: Padded,
+    slots: [UnsafeCell<MaybeUninit<T>>; N],
+}
+
+// Keep the producer and consumer counters on separate cache lines.
+#[repr(C, align(64))]
+struct Padded(AtomicUsize);
+
+/// Pushing half of a [`SpscRing`].
+pub struct SpscProducer<'ring, T, const N: usize> {
+    ring: &'ring SpscRing<T, N>,
+}
+
+/// Popping half of a [`SpscRing`].
+pub struct SpscConsumer<'ring, T, const N: usize> {
+    ring: &'ring SpscRing<T, N>,
+}
+
+impl<T: Copy, const N: usize> SpscRing<T, N> {
+    const NONEMPTY: () = assert!(N > 0, "SpscRing capacity must be positive");
+
+    /// Reset the ring to empty, e.g. as the `init` argument of [`Shm::builder`].
+    pub fn init(ring: &mut MaybeUninit<Self>) {
+        let ring = ring.as_mut_ptr();
+        unsafe {
+            (&raw mut (*ring).head).write(Padded(AtomicUsize::new(0)));
+            (&raw mut (*ring).tail).write(Padded(AtomicUsize::new(0)));
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        let tail = self.tail.0.load(Ordering::Acquire);
+        let head = self.head.0.load(Ordering::Acquire);
+        tail.wrapping_sub(head)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn slot(&self, index: usize) -> *mut MaybeUninit<T> {
+        self.slots[index % N].get()
+    }
+}
+
+impl<T: Copy, const N: usize> Shm<SpscRing<T, N>> {
+    /// # Safety
+    ///
+    /// The caller must ensure at most one producer exists at a time, across
+    /// all processes sharing the segment.
+    pub unsafe fn producer(&self) -> SpscProducer<'_, T, N> {
+        let () = SpscRing::<T, N>::NONEMPTY;
+        SpscProducer {
+            ring: unsafe { self.get() },
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The caller must ensure at most one consumer exists at a time, across
+    /// all processes sharing the segment.
+    pub unsafe fn consumer(&self) -> SpscConsumer<'_, T, N> {
+        let () = SpscRing::<T, N>::NONEMPTY;
+        SpscConsumer {
+            ring: unsafe { self.get() },
+        }
+    }
+}
+
+impl<T: Copy, const N: usize> SpscProducer<'_, T, N> {
+    /// Push `value` onto the back, returning it back if the ring is full.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        let tail = self.ring.tail.0.load(Ordering::Relaxed);
+        if tail.wrapping_sub(self.ring.head.0.load(Ordering::Acquire)) == N {
+            return Err(value);
+        }
+
+        unsafe { self.ring.slot(tail).write(MaybeUninit::new(value)) };
+        self.ring
+            .tail
+            .0
+            .store(tail.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+}
+
+impl<T: Copy, const N: usize> SpscConsumer<'_, T, N> {
+    /// Pop from the front, or `None` if the ring is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let head = self.ring.head.0.load(Ordering::Relaxed);
+        if head == self.ring.tail.0.load(Ordering::Acquire) {
+            return None;
+        }
+
+        let value = unsafe { self.ring.slot(head).read().assume_init() };
+        self.ring
+            .head
+            .0
+            .store(head.wrapping_add(1), Ordering::Release);
+        Some(value)
+    }
+}
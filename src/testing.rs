@@ -0,0 +1,69 @@
+//! Fixtures shared by multi-process tests.
+
+use core::sync::atomic::AtomicU32;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+
+use crate::Raw;
+
+/// Segment name unique to `module` and this test process.
+pub(crate) fn name(module: &str, suffix: &str) -> String {
+    format!("shm-test-{module}-{}-{suffix}", std::process::id())
+}
+
+/// Run `body` in a forked child. A panic exits the child with status 1 instead of
+/// unwinding into its copy of the test harness. See [`wait`].
+pub(crate) fn fork_child(body: impl FnOnce()) -> libc::pid_t {
+    match unsafe { libc::fork() } {
+        -1 => panic!("fork failed: {}", std::io::Error::last_os_error()),
+        0 => {
+            let status = match panic::catch_unwind(AssertUnwindSafe(body)) {
+                Ok(()) => 0,
+                Err(_) => 1,
+            };
+            unsafe { libc::_exit(status) }
+        }
+        pid => pid,
+    }
+}
+
+/// Wait for children from [`fork_child`], asserting that each exited successfully.
+pub(crate) fn wait(children: impl IntoIterator<Item = libc::pid_t>) {
+    for pid in children {
+        let mut status = 0;
+        assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+        assert!(
+            libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0,
+            "child {pid} failed with status {status:#x}"
+        );
+    }
+}
+
+/// Shared atomic counters, e.g. for children to record how many times each
+/// element was claimed. Unlinked when dropped.
+pub(crate) struct Counters {
+    segment: Raw,
+    len: usize,
+}
+
+impl Counters {
+    pub(crate) fn new(name: String, len: usize) -> Self {
+        let segment = Raw::named(name, len * size_of::<AtomicU32>(), true).unwrap();
+        Self { segment, len }
+    }
+
+    pub(crate) fn as_slice(&self) -> &[AtomicU32] {
+        unsafe {
+            core::slice::from_raw_parts(
+                self.segment.address().cast::<AtomicU32>().as_ptr(),
+                self.len,
+            )
+        }
+    }
+}
+
+impl Drop for Counters {
+    fn drop(&mut self) {
+        let _ = self.segment.unlink();
+    }
+}